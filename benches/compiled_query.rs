@@ -0,0 +1,58 @@
+//! Evaluation of the same query over many documents, walking the AST with
+//! the evaluator against running the closures of a compiled query.
+//!
+//! ```text
+//! cargo bench --bench compiled_query
+//! ```
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use ql::{ast::AST, evaluator::CompiledQuery, Evaluator, Tokenizer};
+use serde_json::{json, Value};
+
+const QUERY: &str = r#"laptop brand:acme price:100..2000 -tags:used any(variants, v => v.stock > 0 && v.size = "m")"#;
+
+fn documents() -> Vec<Value> {
+    (0..1000)
+        .map(|i| {
+            let brand = ["acme", "bolt", "core"][i % 3];
+            let tags = if i % 5 == 0 {
+                vec!["used"]
+            } else {
+                vec!["new", "sale"]
+            };
+            json!({
+                "title": format!("Laptop model {i}"),
+                "brand": brand,
+                "price": (i * 7) % 2500,
+                "tags": tags,
+                "variants": [
+                    { "size": "s", "stock": i % 2 },
+                    { "size": "m", "stock": i % 4 },
+                ],
+            })
+        })
+        .collect()
+}
+
+fn compiled_query(c: &mut Criterion) {
+    let ast = AST::parse(Tokenizer::lexer(QUERY).unwrap());
+    let documents = documents();
+    let evaluator = Evaluator::default();
+    let compiled = CompiledQuery::compile(&ast);
+    assert_eq!(
+        evaluator.filter(&ast, &documents).unwrap(),
+        compiled.filter(&documents).unwrap()
+    );
+
+    let mut group = c.benchmark_group("filter");
+    group.bench_function("evaluator", |b| {
+        b.iter(|| evaluator.filter(&ast, &documents).unwrap())
+    });
+    group.bench_function("compiled", |b| {
+        b.iter(|| compiled.filter(&documents).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, compiled_query);
+criterion_main!(benches);
@@ -0,0 +1,111 @@
+//! `#[derive(Queryable)]` for structs with named fields, see
+//! `ql::queryable`.
+//!
+//! Each field is queried by its name, or the name of
+//! `#[queryable(rename = "...")]`, and `#[queryable(skip)]` hides a field
+//! from queries. The type of every other field must be `Queryable`.
+//!
+//! The generated code names the items of `ql` by absolute paths like
+//! `::ql::queryable::Queryable`, so it only compiles where `ql` is a direct
+//! dependency under that name.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, LitStr};
+
+#[proc_macro_derive(Queryable, attributes(queryable))]
+pub fn derive_queryable(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "Queryable can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "Queryable can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let mut names = vec![];
+    let mut idents = vec![];
+    let mut types = vec![];
+    for field in fields {
+        let ident = field.ident.clone().unwrap();
+        let mut name = ident.to_string();
+        let mut skip = false;
+        for attribute in &field.attrs {
+            if !attribute.path().is_ident("queryable") {
+                continue;
+            }
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename` or `skip`"))
+                }
+            })?;
+        }
+        if !skip {
+            names.push(name);
+            idents.push(ident);
+            types.push(field.ty.clone());
+        }
+    }
+
+    for parameter in input.generics.type_params_mut() {
+        parameter
+            .bounds
+            .push(parse_quote!(::ql::queryable::Queryable));
+    }
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::ql::queryable::Queryable for #name #type_generics #where_clause {
+            fn get_field(&self, path: &[String]) -> Option<::ql::queryable::ValueRef<'_>> {
+                match path.split_first() {
+                    None => Some(::ql::queryable::ValueRef::Object(vec![
+                        #((
+                            #names,
+                            ::ql::queryable::Queryable::get_field(&self.#idents, &[])
+                                .unwrap_or(::ql::queryable::ValueRef::Null),
+                        )),*
+                    ])),
+                    Some((segment, rest)) => match segment.as_str() {
+                        #(#names => ::ql::queryable::Queryable::get_field(&self.#idents, rest),)*
+                        _ => None,
+                    },
+                }
+            }
+
+            fn has_field(path: &[String]) -> bool {
+                match path.split_first() {
+                    None => true,
+                    Some((segment, rest)) => match segment.as_str() {
+                        #(#names => <#types as ::ql::queryable::Queryable>::has_field(rest),)*
+                        _ => false,
+                    },
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,75 @@
+//! Text analysis of the free-text fields.
+//!
+//! An [`Analyzer`] splits a text into tokens and filters each token into the
+//! term it is matched and indexed as. [`TextAnalyzer`] chains a
+//! [`WordTokenizer`] with [`TokenFilter`]s, like lowercase, ASCII folding,
+//! stopwords and an English stemmer.
+//!
+//! Terms are compared in two forms:
+//!
+//! - Normalized, by [`Analyzer::normalize`], with the characters changed
+//!   like lowercase or folding `é` into `e`, but without dropping or stemming
+//!   tokens. Free text is looked up in the normalized text, and wildcards and
+//!   fuzzy terms match normalized tokens.
+//! - Analyzed, by [`Analyzer::filter`], the term of a token after every
+//!   filter. Phrases, ranking and the index compare analyzed terms, so
+//!   `"running shoes"` matches `Run shoe` with a stemmer.
+//!
+//! ```text
+//! "Café Running"   normalized `café running`, `cafe running` with folding
+//!                  analyzed `cafe`, `run` with folding and stemming
+//! ```
+//!
+//! The analyzer of a field is set in
+//! [`crate::evaluator::EvaluatorOptions::set_analyzer`], the evaluator, the
+//! ranking and the index all use it.
+
+mod folding;
+mod stemmer;
+mod text_analyzer;
+mod token_filter;
+mod word_tokenizer;
+
+pub use self::text_analyzer::TextAnalyzer;
+pub use self::token_filter::TokenFilter;
+pub use self::word_tokenizer::WordTokenizer;
+
+use std::fmt::Debug;
+use std::ops::Range;
+
+pub trait Analyzer: Debug + Send + Sync {
+    /// Byte ranges of the tokens of a text.
+    fn token_ranges(&self, text: &str) -> Vec<Range<usize>>;
+
+    /// Tokens of a text as written.
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        self.token_ranges(text)
+            .into_iter()
+            .map(|range| text[range].to_string())
+            .collect()
+    }
+
+    /// The text with the characters of its tokens changed, like lowercase,
+    /// and nothing dropped. `filter(normalize(token))` must be
+    /// `filter(token)`.
+    fn normalize(&self, text: &str) -> String;
+
+    /// The term of a token, `None` when it is dropped like a stopword.
+    fn filter(&self, token: &str) -> Option<String>;
+
+    /// Normalized tokens of a text.
+    fn words(&self, text: &str) -> Vec<String> {
+        self.tokenize(text)
+            .iter()
+            .map(|token| self.normalize(token))
+            .collect()
+    }
+
+    /// Terms of a text.
+    fn analyze(&self, text: &str) -> Vec<String> {
+        self.tokenize(text)
+            .iter()
+            .filter_map(|token| self.filter(token))
+            .collect()
+    }
+}
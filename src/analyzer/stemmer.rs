@@ -0,0 +1,47 @@
+/// Light English stemmer for plurals and the `-ed` and `-ing` endings.
+///
+/// Words of 3 letters or less are left as they are, and an ending is only
+/// stripped when a vowel remains, so `sing` and `need` stay.
+pub(super) fn stem_english(word: &str) -> String {
+    let chars: Vec<char> = word.chars().collect();
+    if chars.len() <= 3 || !chars.iter().all(|c| c.is_alphabetic()) {
+        return word.to_string();
+    }
+
+    let word = if let Some(stem) = word.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if let Some(stem) = word.strip_suffix("sses") {
+        format!("{}ss", stem)
+    } else if word.ends_with("ss") || word.ends_with("us") || word.ends_with("is") {
+        word.to_string()
+    } else if let Some(stem) = word.strip_suffix('s') {
+        stem.to_string()
+    } else {
+        word.to_string()
+    };
+
+    for ending in ["ing", "ed"] {
+        if let Some(stem) = word.strip_suffix(ending).filter(|_| !word.ends_with("eed")) {
+            if stem.chars().count() >= 2 && stem.chars().any(is_vowel) {
+                return undouble(stem);
+            }
+        }
+    }
+    word
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
+
+/// `runn` of `running` into `run`, while `fall` and `miss` keep their
+/// double letter.
+fn undouble(stem: &str) -> String {
+    let chars: Vec<char> = stem.chars().collect();
+    match chars.as_slice() {
+        [.., a, b] if a == b && !is_vowel(*a) && !matches!(a, 'l' | 's' | 'z') => {
+            chars[..chars.len() - 1].iter().collect()
+        }
+        _ => stem.to_string(),
+    }
+}
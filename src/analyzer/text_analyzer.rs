@@ -0,0 +1,60 @@
+use super::{Analyzer, TokenFilter, WordTokenizer};
+use std::ops::Range;
+
+/// An [`Analyzer`] made of a tokenizer and a chain of filters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextAnalyzer {
+    pub tokenizer: WordTokenizer,
+    pub filters: Vec<TokenFilter>,
+}
+
+impl TextAnalyzer {
+    pub fn new(tokenizer: WordTokenizer) -> Self {
+        Self {
+            tokenizer,
+            filters: vec![],
+        }
+    }
+
+    /// Words of letters and digits in lowercase, the analyzer of the fields
+    /// without one.
+    pub fn standard() -> Self {
+        let mut analyzer = Self::new(WordTokenizer::UnicodeWord);
+        analyzer.add_filter(TokenFilter::Lowercase);
+        analyzer
+    }
+
+    /// [`TextAnalyzer::standard`] with ASCII folding, English stopwords and
+    /// stemming.
+    pub fn english() -> Self {
+        let mut analyzer = Self::standard();
+        analyzer
+            .add_filter(TokenFilter::AsciiFolding)
+            .add_filter(TokenFilter::english_stopwords())
+            .add_filter(TokenFilter::EnglishStemmer);
+        analyzer
+    }
+
+    pub fn add_filter(&mut self, filter: TokenFilter) -> &mut Self {
+        self.filters.push(filter);
+        self
+    }
+}
+
+impl Analyzer for TextAnalyzer {
+    fn token_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.tokenizer.token_ranges(text)
+    }
+
+    fn normalize(&self, text: &str) -> String {
+        self.filters
+            .iter()
+            .fold(text.to_string(), |text, filter| filter.normalize(text))
+    }
+
+    fn filter(&self, token: &str) -> Option<String> {
+        self.filters
+            .iter()
+            .try_fold(token.to_string(), |token, filter| filter.filter(token))
+    }
+}
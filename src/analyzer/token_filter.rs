@@ -0,0 +1,51 @@
+use super::folding::fold_ascii;
+use super::stemmer::stem_english;
+use std::collections::HashSet;
+
+/// English stopwords, those of Lucene.
+const ENGLISH_STOPWORDS: [&str; 33] = [
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "if", "in", "into", "is", "it",
+    "no", "not", "of", "on", "or", "such", "that", "the", "their", "then", "there", "these",
+    "they", "this", "to", "was", "will", "with",
+];
+
+/// A step of a [`super::TextAnalyzer`], applied to each token in order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenFilter {
+    Lowercase,
+    /// Letters with diacritics and ligatures into ASCII, `Café` into `Cafe`
+    /// and `Æ` into `AE`.
+    AsciiFolding,
+    /// Drop the tokens of the set, compared after the filters before it.
+    Stopwords(HashSet<String>),
+    /// Strip English plurals and `-ed`/`-ing`, `batteries` into `battery`
+    /// and `running` into `run`. Expects lowercase tokens.
+    EnglishStemmer,
+}
+
+impl TokenFilter {
+    pub fn english_stopwords() -> Self {
+        TokenFilter::Stopwords(ENGLISH_STOPWORDS.iter().map(|s| s.to_string()).collect())
+    }
+
+    /// Filter a token, `None` drops it.
+    pub fn filter(&self, token: String) -> Option<String> {
+        match self {
+            TokenFilter::Lowercase => Some(token.to_lowercase()),
+            TokenFilter::AsciiFolding => Some(fold_ascii(&token)),
+            TokenFilter::Stopwords(stopwords) if stopwords.contains(&token) => None,
+            TokenFilter::Stopwords(_) => Some(token),
+            TokenFilter::EnglishStemmer => Some(stem_english(&token)),
+        }
+    }
+
+    /// Change the characters of a text like [`TokenFilter::filter`], filters
+    /// dropping or stemming tokens leave it as is.
+    pub fn normalize(&self, text: String) -> String {
+        match self {
+            TokenFilter::Lowercase => text.to_lowercase(),
+            TokenFilter::AsciiFolding => fold_ascii(&text),
+            TokenFilter::Stopwords(_) | TokenFilter::EnglishStemmer => text,
+        }
+    }
+}
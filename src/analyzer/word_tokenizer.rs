@@ -0,0 +1,43 @@
+use std::ops::Range;
+
+/// Splits a text into tokens.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WordTokenizer {
+    /// Tokens separated by whitespace, keeping punctuation like `e-mail`.
+    Whitespace,
+    /// Runs of Unicode letters and digits, so `e-mail` is `e` and `mail`.
+    UnicodeWord,
+}
+
+impl WordTokenizer {
+    /// Byte ranges of the tokens of a text.
+    pub fn token_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let separator = |c: char| match self {
+            WordTokenizer::Whitespace => c.is_whitespace(),
+            WordTokenizer::UnicodeWord => !c.is_alphanumeric(),
+        };
+        let mut ranges = vec![];
+        let mut start = None;
+        for (index, c) in text.char_indices() {
+            match (separator(c), start) {
+                (true, Some(from)) => {
+                    ranges.push(from..index);
+                    start = None;
+                }
+                (false, None) => start = Some(index),
+                _ => {}
+            }
+        }
+        if let Some(from) = start {
+            ranges.push(from..text.len());
+        }
+        ranges
+    }
+
+    pub fn tokenize(&self, text: &str) -> Vec<String> {
+        self.token_ranges(text)
+            .into_iter()
+            .map(|range| text[range].to_string())
+            .collect()
+    }
+}
@@ -1,5 +1,14 @@
-mod statement;
-mod value;
+mod parser_options;
+mod pipeline;
+mod select;
+mod sort_key;
+mod stage;
+pub mod statement;
+pub mod value;
+
+pub use self::parser_options::ParserOptions;
+pub use self::sort_key::SortKey;
+pub use self::stage::Stage;
 
 use crate::tokenizer::token::{Span, Token};
 use statement::Statement;
@@ -11,6 +20,13 @@ use self::value::Value;
 struct IterToken<'a> {
     index: usize,
     tokens: &'a Vec<Token>,
+    options: &'a ParserOptions,
+    /// Words ending the free text of a clause, like `ORDER` in
+    /// `SELECT * FROM cars WHERE red ORDER BY price`.
+    clauses: &'static [&'static str],
+    /// Keys of the `sort:-price` qualifiers of the search box, `None` where
+    /// they aren't allowed.
+    sort_keys: Option<Vec<SortKey>>,
 }
 
 impl<'a> IterToken<'a> {
@@ -24,7 +40,7 @@ impl<'a> IterToken<'a> {
             None
         }
     }
-    fn current_with_kind<A: ToString>(&self, compare: A) -> Option<&Token> {
+    fn current_with_kind<A: ToString>(&self, compare: A) -> Option<&'a Token> {
         if let Some(token) = self.current() {
             if compare.to_string() == token.kind {
                 return Some(token);
@@ -40,13 +56,69 @@ impl<'a> IterToken<'a> {
             false
         }
     }
+    fn current_is_kind_raw<A: ToString, B: ToString>(&self, kind: A, raw: B) -> bool {
+        if let Some(k) = self.current() {
+            kind.to_string() == k.kind && raw.to_string() == k.raw
+        } else {
+            false
+        }
+    }
+    fn next(&self) -> Option<&'a Token> {
+        self.tokens.get(self.index + 1)
+    }
     fn next_is_kind<A: ToString>(&self, compare: A) -> bool {
-        if let Some(k) = self.tokens.get(self.index + 1) {
+        if let Some(k) = self.next() {
             compare.to_string() == k.kind
         } else {
             false
         }
     }
+    /// Test if the current token is the word `keyword`, in any case, used as
+    /// a logical operator, that is, with a term after it.
+    fn current_is_boolean_keyword(&self, keyword: &str) -> bool {
+        if !self.options.boolean_keywords {
+            return false;
+        }
+        match (self.current(), self.next()) {
+            (Some(k), Some(next)) => {
+                k.kind == "keyword"
+                    && k.raw.eq_ignore_ascii_case(keyword)
+                    && !matches!(next.kind.as_str(), "newline" | "close_parenthesis")
+            }
+            _ => false,
+        }
+    }
+    fn current_is_clause(&self) -> bool {
+        if let Some(k) = self.current() {
+            k.kind == "keyword"
+                && self
+                    .clauses
+                    .iter()
+                    .any(|clause| k.raw.eq_ignore_ascii_case(clause))
+        } else {
+            false
+        }
+    }
+    /// `sort:price` qualifier of the search box, so a field named `sort`
+    /// can't be compared with `:`.
+    fn current_is_sort_qualifier(&self) -> bool {
+        self.sort_keys.is_some()
+            && self
+                .current()
+                .is_some_and(|token| token.kind == "keyword" && token.raw == "sort")
+            && self.next_is_kind("colon")
+    }
+
+    /// `|` between the stages of a pipeline, `||` is the `OR` operator.
+    fn current_is_pipe(&self) -> bool {
+        self.current_is_kind_raw("operation", "|")
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.current_is_kind("newline") {
+            self.forward();
+        }
+    }
     fn forward(&mut self) {
         self.index = self.index.add(1usize);
     }
@@ -55,8 +127,24 @@ impl<'a> IterToken<'a> {
 #[derive(Debug)]
 pub struct AST(Statement);
 
+const QUANTIFIERS: [&str; 3] = ["any", "all", "none"];
+const COMPARISONS: [&str; 4] = [">", "<", ">=", "<="];
+/// Edits a fuzzy term allows at most, like Lucene. A larger distance
+/// matches nearly any word and is clamped to it.
+const MAX_FUZZY_DISTANCE: usize = 2;
+/// Functions of the aggregates of a pipeline, they can't be registered.
+pub(crate) const AGGREGATES: [&str; 6] = ["count", "sum", "avg", "min", "max", "distinct_count"];
+
 impl AST {
+    pub fn statement(&self) -> &Statement {
+        &self.0
+    }
+
     pub fn parse(tokens: Vec<Token>) -> Self {
+        Self::parse_with_options(tokens, &ParserOptions::default())
+    }
+
+    pub fn parse_with_options(tokens: Vec<Token>, options: &ParserOptions) -> Self {
         let first_span: usize = if let Some(token) = tokens.first() {
             token.span.start
         } else {
@@ -71,29 +159,102 @@ impl AST {
         let ref mut iter = IterToken {
             index: 0,
             tokens: &tokens,
+            options,
+            clauses: &[],
+            sort_keys: None,
         };
+        iter.skip_newlines();
+        if iter.current_is_kind("keyword")
+            && iter.current().unwrap().raw.eq_ignore_ascii_case("select")
+        {
+            let select = Self::parse_select(iter);
+            iter.skip_newlines();
+            if let Some(kind) = iter.current_kind() {
+                panic!("kind not supported {:?}", kind);
+            }
+            return AST(select);
+        }
+        iter.sort_keys = Some(vec![]);
         let main_statements: Vec<Box<Statement>> = Self::parse_main(iter);
+        let sort_keys = iter.sort_keys.take().unwrap_or_default();
+        let mut stages = Self::parse_stages(iter);
+        if let Some(kind) = iter.current_kind() {
+            panic!("kind not supported {:?}", kind);
+        }
+
+        if let (Some(first), Some(last)) = (sort_keys.first(), sort_keys.last()) {
+            stages.insert(
+                0,
+                Stage::Sort {
+                    span: Span {
+                        start: first.span.start,
+                        end: last.span.end,
+                    },
+                    keys: sort_keys,
+                },
+            );
+        }
 
-        AST(Statement::Expression {
+        // Without stages the expression spans the whole payload.
+        let end = match stages.is_empty() {
+            true => last_span,
+            false => main_statements
+                .last()
+                .map_or(first_span, |statement| statement.clone().to_span().end),
+        };
+        let source = Statement::Expression {
             span: Span {
                 start: first_span,
-                end: last_span,
+                end,
             },
             expresssion: main_statements,
+        };
+        if stages.is_empty() {
+            return AST(source);
+        }
+
+        AST(Statement::Pipeline {
+            span: Span {
+                start: first_span,
+                end: last_span,
+            },
+            source: Box::new(source),
+            stages,
         })
     }
 
     fn parse_main(iter_tokens: &mut IterToken) -> Vec<Box<Statement>> {
         let mut statemens: Vec<Box<Statement>> = vec![];
+        let mut last_is_word = false;
 
         loop {
             if let Some(token_kind) = iter_tokens.current_kind() {
                 match token_kind {
-                    "keyword" => {
-                        statemens.push(Box::new(Self::parse_indetifier(iter_tokens)));
+                    "newline" => {
+                        iter_tokens.forward();
+                        last_is_word = false;
+                    }
+                    "close_parenthesis" => break,
+                    _ if iter_tokens.current_is_clause() || iter_tokens.current_is_pipe() => break,
+                    _ if iter_tokens.current_is_sort_qualifier() => {
+                        Self::parse_sort_qualifier(iter_tokens);
+                        last_is_word = false;
                     }
                     _ => {
-                        panic!("kind not supported {:?}", iter_tokens.current_kind());
+                        let statement = Self::parse_or(iter_tokens);
+                        let is_word = matches!(
+                            statement,
+                            Statement::Identifier { .. } | Statement::Text { .. }
+                        );
+                        let text = Self::to_text(statement);
+                        if let (true, true, Some(last)) =
+                            (is_word, last_is_word, statemens.last_mut())
+                        {
+                            **last = Self::join_text(last, &text);
+                        } else {
+                            statemens.push(Box::new(text));
+                        }
+                        last_is_word = is_word;
                     }
                 }
             } else {
@@ -104,6 +265,685 @@ impl AST {
         return statemens;
     }
 
+    /// `sort:price,-date`, a `-` sorts that key in descending order.
+    fn parse_sort_qualifier(iter_tokens: &mut IterToken) {
+        iter_tokens.forward();
+        iter_tokens.forward();
+        loop {
+            let start = iter_tokens.current().map_or(0, |token| token.span.start);
+            let descending = iter_tokens.current_is_kind_raw("operation", "-");
+            if descending {
+                iter_tokens.forward();
+            }
+            let expression = Self::parse_indetifier(iter_tokens);
+            let key = SortKey {
+                span: Span {
+                    start,
+                    end: expression.clone().to_span().end,
+                },
+                expression,
+                descending,
+                nulls_first: false,
+            };
+            if let Some(keys) = iter_tokens.sort_keys.as_mut() {
+                keys.push(key);
+            }
+            if !iter_tokens.current_is_kind("comma") {
+                break;
+            }
+            iter_tokens.forward();
+        }
+    }
+
+    /// Terms of the search box written alone are free text, so `search` and
+    /// `"search value"` are looked up on the default fields.
+    fn to_text(statement: Statement) -> Statement {
+        match statement {
+            Statement::Identifier { span, value } => Statement::Text { span, value },
+            Statement::Literal { span, value } => Statement::Text {
+                span,
+                value: match value {
+                    Value::Number(n) => Value::from_str(n),
+                    Value::Boolean(b) => Value::from_str(b),
+                    Value::Null => Value::from_str("null"),
+                    value => value,
+                },
+            },
+            statement => statement,
+        }
+    }
+
+    fn join_text(left: &Statement, right: &Statement) -> Statement {
+        match (left, right) {
+            (
+                Statement::Text {
+                    span: left_span,
+                    value: Value::String(left_value),
+                },
+                Statement::Text {
+                    span: right_span,
+                    value: Value::String(right_value),
+                },
+            ) => Statement::Text {
+                span: Span {
+                    start: left_span.start,
+                    end: right_span.end,
+                },
+                value: Value::from_str(format!("{} {}", left_value, right_value)),
+            },
+            _ => panic!("only text can be joined"),
+        }
+    }
+
+    fn parse_or(iter_tokens: &mut IterToken) -> Statement {
+        let mut left = Self::parse_and(iter_tokens);
+        while iter_tokens.current_is_kind_raw("operation", "||")
+            || iter_tokens.current_is_boolean_keyword("or")
+        {
+            iter_tokens.forward();
+            iter_tokens.skip_newlines();
+            let right = Self::parse_and(iter_tokens);
+            left = Self::binary_expression(Self::to_text(left), "||", Self::to_text(right));
+        }
+        left
+    }
+
+    fn parse_and(iter_tokens: &mut IterToken) -> Statement {
+        let mut left = Self::parse_unary(iter_tokens);
+        while iter_tokens.current_is_kind_raw("operation", "&&")
+            || iter_tokens.current_is_boolean_keyword("and")
+        {
+            iter_tokens.forward();
+            iter_tokens.skip_newlines();
+            let right = Self::parse_unary(iter_tokens);
+            left = Self::binary_expression(Self::to_text(left), "&&", Self::to_text(right));
+        }
+        left
+    }
+
+    /// `-term`, `!field:value` and `NOT field:value` exclude what follows,
+    /// binding tighter than `&&`, `||` and the implicit AND of the terms.
+    fn parse_unary(iter_tokens: &mut IterToken) -> Statement {
+        let token = match iter_tokens.current() {
+            Some(token) if token.kind == "operation" && token.raw == "!" => token,
+            Some(token) if iter_tokens.current_is_boolean_keyword("not") => token,
+            // `- term` with a space isn't an exclusion but a word.
+            Some(token) if token.kind == "operation" && token.raw == "-" => {
+                if iter_tokens
+                    .next()
+                    .is_none_or(|next| next.span.start != token.span.end)
+                {
+                    iter_tokens.forward();
+                    return Statement::Identifier {
+                        span: token.span.clone(),
+                        value: Value::from_str(&token.raw),
+                    };
+                }
+                token
+            }
+            _ => {
+                let condition = Self::parse_comparison(iter_tokens);
+                return Self::parse_boost(iter_tokens, condition);
+            }
+        };
+        iter_tokens.forward();
+
+        let argument = Self::to_text(Self::parse_unary(iter_tokens));
+        Statement::Not {
+            span: Span {
+                start: token.span.start,
+                end: argument.clone().to_span().end,
+            },
+            argument: Box::new(argument),
+        }
+    }
+
+    fn parse_comparison(iter_tokens: &mut IterToken) -> Statement {
+        let left = Self::parse_primary(iter_tokens);
+        let left = Self::parse_fuzzy(iter_tokens, left);
+
+        if iter_tokens.current_is_kind_raw("keyword", "in")
+            && iter_tokens.next_is_kind("open_square_bracket")
+        {
+            iter_tokens.forward();
+            let right = Self::parse_primary(iter_tokens);
+            return Self::binary_expression(left, "in", right);
+        }
+
+        let mut operator = match iter_tokens.current() {
+            Some(token) if token.kind == "equal" && matches!(token.raw.as_str(), "=" | "==") => "=",
+            Some(token)
+                if token.kind == "operation" && COMPARISONS.contains(&token.raw.as_str()) =>
+            {
+                token.raw.as_str()
+            }
+            Some(token) if token.kind == "colon" => ":",
+            Some(token)
+                if token.kind == "operation" && matches!(token.raw.as_str(), "=~" | "!~") =>
+            {
+                token.raw.as_str()
+            }
+            _ => return left,
+        };
+        let operator_token = iter_tokens.current().unwrap();
+        iter_tokens.forward();
+
+        if operator == ":" {
+            // `condition:` without a value is part of the free text.
+            if matches!(
+                iter_tokens.current_kind(),
+                None | Some("newline") | Some("close_parenthesis")
+            ) {
+                if let Statement::Identifier {
+                    span,
+                    value: Value::String(value),
+                } = left
+                {
+                    return Statement::Text {
+                        span: Span {
+                            start: span.start,
+                            end: operator_token.span.end,
+                        },
+                        value: Value::from_str(format!("{}:", value)),
+                    };
+                }
+            }
+
+            // `price:>=10` is `price >= 10`.
+            match iter_tokens.current() {
+                Some(token)
+                    if token.kind == "operation" && COMPARISONS.contains(&token.raw.as_str()) =>
+                {
+                    operator = token.raw.as_str();
+                    iter_tokens.forward();
+                }
+                _ => {
+                    let range = Self::parse_range(iter_tokens);
+                    return Self::binary_expression(left, operator, range);
+                }
+            }
+        }
+
+        let right = Self::parse_primary(iter_tokens);
+        Self::binary_expression(left, operator, right)
+    }
+
+    /// Value of `field:value`, where `value` may be a range like `10..20`,
+    /// `10..*`, `*..20` or `10..<20`.
+    fn parse_range(iter_tokens: &mut IterToken) -> Statement {
+        let start_token = iter_tokens.current();
+        let start = Self::parse_range_bound(iter_tokens);
+        if !iter_tokens.current_is_kind("range") {
+            return match start {
+                Some(start) => {
+                    let value = Self::parse_fuzzy(iter_tokens, start);
+                    Self::to_phrase(&value).unwrap_or(value)
+                }
+                None => panic!("kind not supported {:?}", iter_tokens.current_kind()),
+            };
+        }
+        let range_token = iter_tokens.current().unwrap();
+        iter_tokens.forward();
+        let end_token = iter_tokens.current();
+        let end = Self::parse_range_bound(iter_tokens);
+
+        Statement::RangeExpression {
+            span: Span {
+                start: start_token.unwrap().span.start,
+                end: match &end {
+                    Some(end) => end.clone().to_span().end,
+                    None => end_token.map_or(range_token.span.end, |token| token.span.end),
+                },
+            },
+            start: start.map(Box::new),
+            end: end.map(Box::new),
+            start_exclusive: range_token.raw.starts_with('<'),
+            end_exclusive: range_token.raw.ends_with('<'),
+        }
+    }
+
+    /// `colour~1`, a word or string followed by `~` and the number of edits,
+    /// or `"quick fox"~3`, a phrase followed by the number of words allowed
+    /// between its words.
+    fn parse_fuzzy(iter_tokens: &mut IterToken, term: Statement) -> Statement {
+        let token = match iter_tokens.current_with_kind("fuzzy") {
+            Some(token) => token,
+            None => return term,
+        };
+        iter_tokens.forward();
+        // The lexer only lets digits fitting a `usize` through.
+        let distance = match &token.raw[1..] {
+            "" => MAX_FUZZY_DISTANCE,
+            distance => distance.parse().unwrap_or(usize::MAX),
+        };
+        if let Some(Statement::Phrase { span, phrase, .. }) = Self::to_phrase(&term) {
+            return Statement::Phrase {
+                span: Span {
+                    start: span.start,
+                    end: token.span.end,
+                },
+                phrase,
+                slop: distance,
+            };
+        }
+        let (span, term) = match term {
+            Statement::Identifier {
+                span,
+                value: Value::String(term),
+            }
+            | Statement::Literal {
+                span,
+                value: Value::String(term),
+            } => (span, term),
+            _ => panic!("kind not supported {:?}", Some(&token.kind)),
+        };
+        Statement::Fuzzy {
+            span: Span {
+                start: span.start,
+                end: token.span.end,
+            },
+            term,
+            distance: distance.min(MAX_FUZZY_DISTANCE),
+        }
+    }
+
+    /// `quick^2` or `title:fox^0.5`, a condition followed by `^` and the
+    /// weight of its terms when scoring. A boosted word is free text.
+    fn parse_boost(iter_tokens: &mut IterToken, argument: Statement) -> Statement {
+        let token = match iter_tokens.current_with_kind("boost") {
+            Some(token) => token,
+            None => return argument,
+        };
+        iter_tokens.forward();
+        Statement::Boost {
+            span: Span {
+                start: argument.clone().to_span().start,
+                end: token.span.end,
+            },
+            argument: Box::new(Self::to_text(argument)),
+            // The lexer only lets numbers through.
+            boost: token.raw[1..].parse().unwrap_or(1.0),
+        }
+    }
+
+    /// A string of several words is a phrase.
+    fn to_phrase(statement: &Statement) -> Option<Statement> {
+        match statement {
+            Statement::Literal {
+                span,
+                value: Value::String(phrase),
+            } if phrase.split_whitespace().count() > 1 => Some(Statement::Phrase {
+                span: span.clone(),
+                phrase: phrase.clone(),
+                slop: 0,
+            }),
+            _ => None,
+        }
+    }
+
+    fn parse_range_bound(iter_tokens: &mut IterToken) -> Option<Statement> {
+        if iter_tokens.current_is_kind_raw("operation", "*") {
+            iter_tokens.forward();
+            return None;
+        }
+        match Self::parse_primary(iter_tokens) {
+            // `status:open` compares with the word `open`, not the field `open`.
+            Statement::Identifier { span, value } => Some(Statement::Literal { span, value }),
+            bound => Some(bound),
+        }
+    }
+
+    fn binary_expression(left: Statement, operator: &str, right: Statement) -> Statement {
+        Statement::BinaryExpression {
+            span: Span {
+                start: left.clone().to_span().start,
+                end: right.clone().to_span().end,
+            },
+            left: Box::new(left),
+            operator: operator.to_string(),
+            right: Box::new(right),
+        }
+    }
+
+    fn parse_primary(iter_tokens: &mut IterToken) -> Statement {
+        if let Some(token) = iter_tokens.current() {
+            match token.kind.as_str() {
+                "keyword"
+                    if QUANTIFIERS.contains(&token.raw.as_str())
+                        && iter_tokens.next_is_kind("open_parenthesis") =>
+                {
+                    return Self::parse_quantifier(iter_tokens);
+                }
+                "keyword"
+                    if iter_tokens.next().is_some_and(|next| {
+                        next.kind == "open_parenthesis" && next.span.start == token.span.end
+                    }) =>
+                {
+                    if AGGREGATES.contains(&token.raw.as_str()) {
+                        return Self::parse_aggregate(iter_tokens);
+                    }
+                    return Self::parse_call(iter_tokens);
+                }
+                "keyword" if matches!(token.raw.as_str(), "true" | "false" | "null") => {
+                    iter_tokens.forward();
+                    return Statement::Literal {
+                        span: token.span.clone(),
+                        value: match token.raw.as_str() {
+                            "true" => Value::Boolean(true),
+                            "false" => Value::Boolean(false),
+                            _ => Value::Null,
+                        },
+                    };
+                }
+                "keyword" => return Self::parse_indetifier(iter_tokens),
+                "string" | "date" => {
+                    iter_tokens.forward();
+                    return Statement::Literal {
+                        span: token.span.clone(),
+                        value: Value::from_str(decode_helpper(token.raw.clone())),
+                    };
+                }
+                "numeric" => return Self::parse_numeric(iter_tokens),
+                // `-10`, a `-` right before a number where a value is expected.
+                "operation"
+                    if token.raw == "-"
+                        && iter_tokens.next().is_some_and(|next| {
+                            next.kind == "numeric" && next.span.start == token.span.end
+                        }) =>
+                {
+                    iter_tokens.forward();
+                    return match Self::parse_numeric(iter_tokens) {
+                        Statement::Literal {
+                            span,
+                            value: Value::Number(number),
+                        } => Statement::Literal {
+                            span: Span {
+                                start: token.span.start,
+                                end: span.end,
+                            },
+                            value: Value::Number(-number),
+                        },
+                        statement => statement,
+                    };
+                }
+                "wildcard" => {
+                    iter_tokens.forward();
+                    return Statement::Wildcard {
+                        span: token.span.clone(),
+                        pattern: token.raw.clone(),
+                    };
+                }
+                "regex" => {
+                    iter_tokens.forward();
+                    return Statement::Literal {
+                        span: token.span.clone(),
+                        value: decode_regex(&token.raw),
+                    };
+                }
+                "open_parenthesis" => return Self::parse_group(iter_tokens),
+                "open_square_bracket" => return Self::parse_array(iter_tokens),
+                "open_curly_bracket" => return Self::parse_object(iter_tokens),
+                "template_start" => return Self::parse_template(iter_tokens),
+                _ => {}
+            }
+        }
+
+        panic!("kind not supported {:?}", iter_tokens.current_kind());
+    }
+
+    fn parse_numeric(iter_tokens: &mut IterToken) -> Statement {
+        let token = iter_tokens.current_with_kind("numeric").unwrap();
+        let mut raw = token.raw.clone();
+        let mut end = token.span.end;
+        iter_tokens.forward();
+
+        // The tokenizer splits `9.12` into `9`, `.` and `12`.
+        if let (Some(dot), Some(decimals)) = (iter_tokens.current(), iter_tokens.next()) {
+            if dot.kind == "dot"
+                && decimals.kind == "numeric"
+                && dot.span.start == end
+                && decimals.span.start == dot.span.end
+            {
+                raw = format!("{}.{}", raw, decimals.raw);
+                end = decimals.span.end;
+                iter_tokens.forward();
+                iter_tokens.forward();
+            }
+        }
+
+        Statement::Literal {
+            span: Span {
+                start: token.span.start,
+                end,
+            },
+            value: Value::Number(raw.replace('_', "").parse().unwrap()),
+        }
+    }
+
+    fn parse_group(iter_tokens: &mut IterToken) -> Statement {
+        let start = iter_tokens.current().unwrap().span.start;
+        iter_tokens.forward();
+        let statements = Self::parse_main(iter_tokens);
+        let close = Self::expect_kind(iter_tokens, "close_parenthesis");
+        Statement::Expression {
+            span: Span {
+                start,
+                end: close.span.end,
+            },
+            expresssion: statements,
+        }
+    }
+
+    fn parse_array(iter_tokens: &mut IterToken) -> Statement {
+        let start = iter_tokens.current().unwrap().span.start;
+        iter_tokens.forward();
+        let mut elements = vec![];
+        loop {
+            iter_tokens.skip_newlines();
+            if iter_tokens.current_is_kind("close_square_bracket") {
+                break;
+            }
+            elements.push(Self::parse_or(iter_tokens));
+            iter_tokens.skip_newlines();
+            if iter_tokens.current_is_kind("comma") {
+                iter_tokens.forward();
+            }
+        }
+        let close = Self::expect_kind(iter_tokens, "close_square_bracket");
+        Statement::ArrayExpression {
+            span: Span {
+                start,
+                end: close.span.end,
+            },
+            elements,
+        }
+    }
+
+    /// `{ name, price: $.pricing.amount }`, a property without a key is
+    /// named by the last segment of its path.
+    fn parse_object(iter_tokens: &mut IterToken) -> Statement {
+        let start = iter_tokens.current().unwrap().span.start;
+        iter_tokens.forward();
+        let mut properties = vec![];
+        loop {
+            iter_tokens.skip_newlines();
+            if iter_tokens.current_is_kind("close_curly_bracket") {
+                break;
+            }
+            let key = match iter_tokens.current() {
+                Some(token)
+                    if matches!(token.kind.as_str(), "keyword" | "string")
+                        && iter_tokens.next_is_kind("colon") =>
+                {
+                    iter_tokens.forward();
+                    iter_tokens.forward();
+                    Some(decode_helpper(token.raw.clone()))
+                }
+                _ => None,
+            };
+            let value = Self::parse_or(iter_tokens);
+            let key = match key.or_else(|| value.default_name()) {
+                Some(key) => key,
+                None => panic!("property without key {:?}", value),
+            };
+            properties.push((key, value));
+            iter_tokens.skip_newlines();
+            if iter_tokens.current_is_kind("comma") {
+                iter_tokens.forward();
+            }
+        }
+        let close = Self::expect_kind(iter_tokens, "close_curly_bracket");
+        Statement::ObjectExpression {
+            span: Span {
+                start,
+                end: close.span.end,
+            },
+            properties,
+        }
+    }
+
+    /// `` `${brand} ${model}` ``, the text between the expressions is
+    /// unescaped.
+    fn parse_template(iter_tokens: &mut IterToken) -> Statement {
+        let start = Self::expect_kind(iter_tokens, "template_start").span.start;
+        let mut quasis = vec![decode_helpper(
+            Self::expect_kind(iter_tokens, "template").raw.clone(),
+        )];
+        let mut expressions = vec![];
+        while iter_tokens.current_is_kind("template_start_expression") {
+            iter_tokens.forward();
+            iter_tokens.skip_newlines();
+            expressions.push(Self::parse_or(iter_tokens));
+            iter_tokens.skip_newlines();
+            Self::expect_kind(iter_tokens, "template_close_expression");
+            quasis.push(decode_helpper(
+                Self::expect_kind(iter_tokens, "template").raw.clone(),
+            ));
+        }
+        let close = Self::expect_kind(iter_tokens, "template_close");
+        Statement::TemplateLiteral {
+            span: Span {
+                start,
+                end: close.span.end,
+            },
+            quasis,
+            expressions,
+        }
+    }
+
+    /// `count()`, `count(field)` or `sum(field)`, like a call with at most
+    /// one argument, required by all but `count`.
+    fn parse_aggregate(iter_tokens: &mut IterToken) -> Statement {
+        match Self::parse_call(iter_tokens) {
+            Statement::Call {
+                span,
+                callee,
+                mut arguments,
+            } if arguments.len() == 1 || (arguments.is_empty() && callee == "count") => {
+                Statement::Aggregate {
+                    span,
+                    function: callee,
+                    argument: arguments.pop().map(Box::new),
+                }
+            }
+            statement => panic!("aggregate arguments not supported {:?}", statement),
+        }
+    }
+
+    fn parse_call(iter_tokens: &mut IterToken) -> Statement {
+        let token = iter_tokens.current().unwrap();
+        iter_tokens.forward();
+        iter_tokens.forward();
+        let mut arguments = vec![];
+        loop {
+            iter_tokens.skip_newlines();
+            if iter_tokens.current_is_kind("close_parenthesis") {
+                break;
+            }
+            arguments.push(Self::parse_or(iter_tokens));
+            iter_tokens.skip_newlines();
+            if iter_tokens.current_is_kind("comma") {
+                iter_tokens.forward();
+            }
+        }
+        let close = Self::expect_kind(iter_tokens, "close_parenthesis");
+        Statement::Call {
+            span: Span {
+                start: token.span.start,
+                end: close.span.end,
+            },
+            callee: token.raw.clone(),
+            arguments,
+        }
+    }
+
+    fn parse_quantifier(iter_tokens: &mut IterToken) -> Statement {
+        let token = iter_tokens.current().unwrap();
+        iter_tokens.forward();
+        iter_tokens.forward();
+        iter_tokens.skip_newlines();
+
+        let first = Self::parse_or(iter_tokens);
+        iter_tokens.skip_newlines();
+
+        let (collection, binding, predicate) = if iter_tokens.current_is_kind("comma") {
+            iter_tokens.forward();
+            iter_tokens.skip_newlines();
+            let binding =
+                if iter_tokens.current_is_kind("keyword") && iter_tokens.next_is_kind("arrow") {
+                    let binding = iter_tokens.current().unwrap().raw.clone();
+                    iter_tokens.forward();
+                    iter_tokens.forward();
+                    Some(binding)
+                } else {
+                    None
+                };
+            let predicate = Self::parse_or(iter_tokens);
+            iter_tokens.skip_newlines();
+            (Some(Box::new(first)), binding, predicate)
+        } else {
+            (None, None, first)
+        };
+
+        let close = Self::expect_kind(iter_tokens, "close_parenthesis");
+
+        Statement::Quantifier {
+            span: Span {
+                start: token.span.start,
+                end: close.span.end,
+            },
+            quantifier: token.raw.clone(),
+            collection,
+            binding,
+            predicate: Box::new(predicate),
+        }
+    }
+
+    /// Count of `LIMIT 10` or `| offset 1_000`, a count too large for a
+    /// `usize` is `usize::MAX` as no collection is that long.
+    fn parse_count(token: &Token) -> usize {
+        let count = token.raw.replace('_', "");
+        if count.is_empty() || !count.bytes().all(|byte| byte.is_ascii_digit()) {
+            panic!("expected a count but found {:?}", token.raw);
+        }
+        count.parse().unwrap_or(usize::MAX)
+    }
+
+    fn expect_kind<'a>(iter_tokens: &mut IterToken<'a>, kind: &str) -> &'a Token {
+        if let Some(token) = iter_tokens.current() {
+            if token.kind == kind {
+                iter_tokens.forward();
+                return token;
+            }
+        }
+
+        panic!(
+            "expected {} but found {:?}",
+            kind,
+            iter_tokens.current_kind()
+        );
+    }
+
     fn parse_indetifier(iter_tokens: &mut IterToken) -> Statement {
         if let Some(token) = iter_tokens.current_with_kind("keyword") {
             let identifier = Statement::Identifier {
@@ -149,7 +989,6 @@ impl AST {
             object: Box::new(object.clone()),
             property: Box::new(p),
         };
-        iter_tokens.forward();
         return m;
     }
 
@@ -173,6 +1012,13 @@ impl AST {
     }
 }
 
+/// `/a\/b/i` is the pattern `a/b` with the flag `i`.
+fn decode_regex(raw: &str) -> Value {
+    let close = raw.rfind('/').unwrap_or(raw.len());
+    let pattern = raw.get(1..close).unwrap_or_default().replace("\\/", "/");
+    Value::Regex(pattern, raw[close + 1..].to_string())
+}
+
 fn decode_helpper(str: String) -> String {
     let mut d = "".to_string();
     let mut cursor = str.as_bytes().iter();
@@ -0,0 +1,22 @@
+#[derive(Debug, Clone)]
+pub struct ParserOptions {
+    /// Read the words `AND`, `OR` and `NOT`, in any case, as logical
+    /// operators when they appear between terms. Disabled, they are free
+    /// text like any other word. Quoted, like `"OR"`, they are always text.
+    pub boolean_keywords: bool,
+}
+
+impl ParserOptions {
+    pub fn set_boolean_keywords(&mut self, boolean_keywords: bool) -> &mut Self {
+        self.boolean_keywords = boolean_keywords;
+        self
+    }
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            boolean_keywords: true,
+        }
+    }
+}
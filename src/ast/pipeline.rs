@@ -0,0 +1,120 @@
+use super::stage::Stage;
+use super::statement::Statement;
+use super::{IterToken, AGGREGATES, AST};
+use crate::tokenizer::token::Span;
+
+impl AST {
+    /// Stages after each `|` of `status:open | sort price desc | limit 10`.
+    pub(super) fn parse_stages(iter_tokens: &mut IterToken) -> Vec<Stage> {
+        let mut stages = vec![];
+        while iter_tokens.current_is_pipe() {
+            iter_tokens.forward();
+            iter_tokens.skip_newlines();
+            if iter_tokens.current_is_kind("open_curly_bracket") {
+                match Self::parse_object(iter_tokens) {
+                    Statement::ObjectExpression { span, properties } => stages.push(Stage::Pick {
+                        span,
+                        fields: properties,
+                    }),
+                    _ => unreachable!(),
+                }
+                iter_tokens.skip_newlines();
+                continue;
+            }
+            if Self::current_is_aggregate(iter_tokens) {
+                let aggregates = Self::parse_projection_items(iter_tokens);
+                let end = aggregates
+                    .last()
+                    .map_or(0, |(_, aggregate)| aggregate.clone().to_span().end);
+                // `group by brand | count()` is a single stage.
+                match stages.last_mut() {
+                    Some(Stage::Group {
+                        span,
+                        aggregates: group_aggregates,
+                        ..
+                    }) if group_aggregates.is_empty() => {
+                        span.end = end;
+                        *group_aggregates = aggregates;
+                    }
+                    _ => {
+                        let start = aggregates
+                            .first()
+                            .map_or(0, |(_, aggregate)| aggregate.clone().to_span().start);
+                        stages.push(Stage::Group {
+                            span: Span { start, end },
+                            keys: vec![],
+                            aggregates,
+                        });
+                    }
+                }
+                iter_tokens.skip_newlines();
+                continue;
+            }
+            let name = Self::expect_kind(iter_tokens, "keyword");
+            let stage = match name.raw.to_lowercase().as_str() {
+                "sort" => {
+                    let keys = Self::parse_sort_keys(iter_tokens);
+                    let end = keys.last().map_or(name.span.end, |key| key.span.end);
+                    Stage::Sort {
+                        span: Span {
+                            start: name.span.start,
+                            end,
+                        },
+                        keys,
+                    }
+                }
+                "limit" | "offset" => {
+                    let token = Self::expect_kind(iter_tokens, "numeric");
+                    let span = Span {
+                        start: name.span.start,
+                        end: token.span.end,
+                    };
+                    let count = Self::parse_count(token);
+                    match name.raw.to_lowercase().as_str() {
+                        "limit" => Stage::Limit { span, count },
+                        _ => Stage::Offset { span, count },
+                    }
+                }
+                "group" => {
+                    Self::expect_keyword(iter_tokens, "by");
+                    let keys = Self::parse_projection_items(iter_tokens);
+                    let end = keys
+                        .last()
+                        .map_or(name.span.end, |(_, key)| key.clone().to_span().end);
+                    Stage::Group {
+                        span: Span {
+                            start: name.span.start,
+                            end,
+                        },
+                        keys,
+                        aggregates: vec![],
+                    }
+                }
+                "pick" => {
+                    let fields = Self::parse_projection_items(iter_tokens);
+                    let end = fields
+                        .last()
+                        .map_or(name.span.end, |(_, field)| field.clone().to_span().end);
+                    Stage::Pick {
+                        span: Span {
+                            start: name.span.start,
+                            end,
+                        },
+                        fields,
+                    }
+                }
+                stage => panic!("stage not supported {:?}", stage),
+            };
+            stages.push(stage);
+            iter_tokens.skip_newlines();
+        }
+        stages
+    }
+
+    /// `count(), avg(price)` starting a stage.
+    fn current_is_aggregate(iter_tokens: &IterToken) -> bool {
+        iter_tokens.current().is_some_and(|token| {
+            token.kind == "keyword" && AGGREGATES.contains(&token.raw.as_str())
+        }) && iter_tokens.next_is_kind("open_parenthesis")
+    }
+}
@@ -0,0 +1,201 @@
+use super::sort_key::SortKey;
+use super::statement::Statement;
+use super::{IterToken, AST};
+use crate::tokenizer::token::{Span, Token};
+
+const CLAUSES: [&str; 4] = ["group", "order", "limit", "offset"];
+
+impl AST {
+    /// `SELECT projection FROM collection [WHERE condition] [GROUP BY key, ...]
+    /// [ORDER BY key [ASC|DESC], ...] [LIMIT n] [OFFSET n]`, keywords in any
+    /// case. The condition is the same language of the search box.
+    pub(super) fn parse_select(iter_tokens: &mut IterToken) -> Statement {
+        let select = Self::expect_keyword(iter_tokens, "select");
+        let mut end = select.span.end;
+
+        let projection = if iter_tokens.current_is_kind_raw("operation", "*") {
+            iter_tokens.forward();
+            None
+        } else {
+            Some(Self::parse_projection_items(iter_tokens))
+        };
+
+        iter_tokens.skip_newlines();
+        Self::expect_keyword(iter_tokens, "from");
+        let from = Self::expect_kind(iter_tokens, "keyword");
+        end = end.max(from.span.end);
+        iter_tokens.skip_newlines();
+
+        let r#where = if Self::current_is_keyword(iter_tokens, "where") {
+            let token = Self::expect_keyword(iter_tokens, "where");
+            let clauses = iter_tokens.clauses;
+            iter_tokens.clauses = &CLAUSES;
+            let statements = Self::parse_main(iter_tokens);
+            iter_tokens.clauses = clauses;
+            let where_end = statements
+                .last()
+                .map_or(token.span.end, |statement| statement.clone().to_span().end);
+            end = end.max(where_end);
+            Some(Box::new(Statement::Expression {
+                span: Span {
+                    start: token.span.end,
+                    end: where_end,
+                },
+                expresssion: statements,
+            }))
+        } else {
+            None
+        };
+
+        let mut group_by = vec![];
+        if Self::current_is_keyword(iter_tokens, "group") {
+            iter_tokens.forward();
+            Self::expect_keyword(iter_tokens, "by");
+            loop {
+                iter_tokens.skip_newlines();
+                let key = Self::parse_or(iter_tokens);
+                end = end.max(key.clone().to_span().end);
+                group_by.push(key);
+                iter_tokens.skip_newlines();
+                if !iter_tokens.current_is_kind("comma") {
+                    break;
+                }
+                iter_tokens.forward();
+            }
+        }
+
+        let mut order_by = vec![];
+        if Self::current_is_keyword(iter_tokens, "order") {
+            iter_tokens.forward();
+            Self::expect_keyword(iter_tokens, "by");
+            order_by = Self::parse_sort_keys(iter_tokens);
+            if let Some(key) = order_by.last() {
+                end = end.max(key.span.end);
+            }
+        }
+
+        let limit = Self::parse_select_count(iter_tokens, "limit", &mut end);
+        let offset = Self::parse_select_count(iter_tokens, "offset", &mut end);
+
+        Statement::Select {
+            span: Span {
+                start: select.span.start,
+                end,
+            },
+            projection,
+            from: from.raw.clone(),
+            r#where,
+            group_by,
+            order_by,
+            limit,
+            offset,
+        }
+    }
+
+    /// `item, ...` of [`Self::parse_projection_item`].
+    pub(super) fn parse_projection_items(iter_tokens: &mut IterToken) -> Vec<(String, Statement)> {
+        let mut items = vec![];
+        loop {
+            iter_tokens.skip_newlines();
+            items.push(Self::parse_projection_item(iter_tokens));
+            iter_tokens.skip_newlines();
+            if !iter_tokens.current_is_kind("comma") {
+                break;
+            }
+            iter_tokens.forward();
+        }
+        items
+    }
+
+    /// `expression [AS alias]`, named by the alias or else by
+    /// [`Statement::default_name`].
+    pub(super) fn parse_projection_item(iter_tokens: &mut IterToken) -> (String, Statement) {
+        let expression = Self::parse_or(iter_tokens);
+        let name = if Self::current_is_keyword(iter_tokens, "as") {
+            iter_tokens.forward();
+            Self::expect_kind(iter_tokens, "keyword").raw.clone()
+        } else {
+            match expression.default_name() {
+                Some(name) => name,
+                None => panic!("projection without alias {:?}", expression),
+            }
+        };
+        (name, expression)
+    }
+
+    /// `key [ASC|DESC] [NULLS FIRST|LAST], ...`.
+    pub(super) fn parse_sort_keys(iter_tokens: &mut IterToken) -> Vec<SortKey> {
+        let mut keys = vec![];
+        loop {
+            iter_tokens.skip_newlines();
+            let expression = Self::parse_or(iter_tokens);
+            let mut span = expression.clone().to_span();
+            let descending = if Self::current_is_keyword(iter_tokens, "desc") {
+                span.end = Self::expect_keyword(iter_tokens, "desc").span.end;
+                true
+            } else {
+                if Self::current_is_keyword(iter_tokens, "asc") {
+                    span.end = Self::expect_keyword(iter_tokens, "asc").span.end;
+                }
+                false
+            };
+            let mut nulls_first = false;
+            if Self::current_is_keyword(iter_tokens, "nulls") {
+                iter_tokens.forward();
+                nulls_first = Self::current_is_keyword(iter_tokens, "first");
+                let token = match nulls_first {
+                    true => Self::expect_keyword(iter_tokens, "first"),
+                    false => Self::expect_keyword(iter_tokens, "last"),
+                };
+                span.end = token.span.end;
+            }
+            keys.push(SortKey {
+                span,
+                expression,
+                descending,
+                nulls_first,
+            });
+            iter_tokens.skip_newlines();
+            if !iter_tokens.current_is_kind("comma") {
+                break;
+            }
+            iter_tokens.forward();
+        }
+        keys
+    }
+
+    fn parse_select_count(
+        iter_tokens: &mut IterToken,
+        keyword: &str,
+        end: &mut usize,
+    ) -> Option<usize> {
+        iter_tokens.skip_newlines();
+        if !Self::current_is_keyword(iter_tokens, keyword) {
+            return None;
+        }
+        iter_tokens.forward();
+        let count = Self::expect_kind(iter_tokens, "numeric");
+        *end = (*end).max(count.span.end);
+        Some(Self::parse_count(count))
+    }
+
+    pub(super) fn current_is_keyword(iter_tokens: &IterToken, keyword: &str) -> bool {
+        iter_tokens
+            .current()
+            .is_some_and(|token| token.kind == "keyword" && token.raw.eq_ignore_ascii_case(keyword))
+    }
+
+    pub(super) fn expect_keyword<'a>(iter_tokens: &mut IterToken<'a>, keyword: &str) -> &'a Token {
+        if Self::current_is_keyword(iter_tokens, keyword) {
+            let token = iter_tokens.current().unwrap();
+            iter_tokens.forward();
+            return token;
+        }
+
+        panic!(
+            "expected {} but found {:?}",
+            keyword,
+            iter_tokens.current_kind()
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use super::statement::Statement;
+use crate::tokenizer::token::Span;
+
+/// Key of `ORDER BY price DESC NULLS FIRST`.
+///
+/// `null` and missing values go last unless `NULLS FIRST`, in either
+/// direction.
+#[derive(Debug, Clone)]
+pub struct SortKey {
+    pub span: Span,
+    pub expression: Statement,
+    pub descending: bool,
+    pub nulls_first: bool,
+}
@@ -0,0 +1,40 @@
+use crate::tokenizer::token::Span;
+
+use super::sort_key::SortKey;
+use super::statement::Statement;
+
+/// Stage of a pipeline, `status:open | sort price desc | limit 10 | pick name`.
+#[derive(Debug, Clone)]
+pub enum Stage {
+    /// `sort price desc, name`.
+    Sort { span: Span, keys: Vec<SortKey> },
+    /// `limit 10`, the first documents that reach the stage.
+    Limit { span: Span, count: usize },
+    /// `offset 20`, skips the first documents that reach the stage.
+    Offset { span: Span, count: usize },
+    /// `group by brand | count(), avg(price)`, a row for each group with
+    /// its keys and aggregates. Without `group by` there is a single group.
+    Group {
+        span: Span,
+        keys: Vec<(String, Statement)>,
+        aggregates: Vec<(String, Statement)>,
+    },
+    /// `pick name, price AS p`, named like the projection of a `SELECT`, or
+    /// the object `{ name, p: price }`.
+    Pick {
+        span: Span,
+        fields: Vec<(String, Statement)>,
+    },
+}
+
+impl Stage {
+    pub fn to_span(self) -> Span {
+        match self {
+            Self::Sort { span, .. } => span,
+            Self::Limit { span, .. } => span,
+            Self::Offset { span, .. } => span,
+            Self::Group { span, .. } => span,
+            Self::Pick { span, .. } => span,
+        }
+    }
+}
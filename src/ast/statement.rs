@@ -1,5 +1,7 @@
 use crate::tokenizer::token::Span;
 
+use super::sort_key::SortKey;
+use super::stage::Stage;
 use super::value::Value;
 
 #[derive(Debug, Clone)]
@@ -21,12 +23,136 @@ pub enum Statement {
         object: Box<Self>,
         property: Box<Self>,
     },
-    // BinaryExpression {
-    //     span: Span,
-    //     left: Box<Statement>,
-    //     operator: String,
-    //     rigth: Box<Statement>,
-    // },
+    /// A constant written in the query, like `"value"`, `12.5` or `true`.
+    Literal {
+        span: Span,
+        value: Value,
+    },
+    /// Free text of the search box, matched against the default fields.
+    Text {
+        span: Span,
+        value: Value,
+    },
+    /// `lapt*` or `col?r`, where `*` is any run of characters and `?` is one
+    /// character. Alone it matches a word of the default fields, while in
+    /// `brand:ac*` it matches the whole value.
+    Wildcard {
+        span: Span,
+        pattern: String,
+    },
+    /// `colour~1`, a term matching within `distance` edits, 2 when written
+    /// `colour~` and at most 2. Like [`Self::Wildcard`], alone it matches a
+    /// word of the default fields and in `name:colour~1` the whole value.
+    Fuzzy {
+        span: Span,
+        term: String,
+        distance: usize,
+    },
+    /// `"quick fox"~3`, the words of `phrase` in order with at most `slop`
+    /// words between them in total. `title:"quick fox"` is a phrase with no
+    /// words between, while a phrase written alone without `~` is free text.
+    Phrase {
+        span: Span,
+        phrase: String,
+        slop: usize,
+    },
+    /// `quick^2`, the terms of `argument` weigh `boost` times as much when
+    /// scoring, and it matches like `argument`.
+    Boost {
+        span: Span,
+        argument: Box<Self>,
+        boost: f64,
+    },
+    BinaryExpression {
+        span: Span,
+        left: Box<Self>,
+        operator: String,
+        right: Box<Self>,
+    },
+    /// `SELECT name, price AS p FROM cars WHERE price > 10 ORDER BY price
+    /// DESC LIMIT 10 OFFSET 20`.
+    ///
+    /// Each item of the projection is named by its alias, or else by the
+    /// last segment of its path. `SELECT *` has no projection. With
+    /// `GROUP BY`, or aggregates in the projection, each group is a row and
+    /// `ORDER BY` sorts the rows.
+    Select {
+        span: Span,
+        projection: Option<Vec<(String, Self)>>,
+        from: String,
+        r#where: Option<Box<Self>>,
+        group_by: Vec<Self>,
+        order_by: Vec<SortKey>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    },
+    /// Documents matching `source` streamed through the stages after each
+    /// `|`, `status:open | sort price desc | limit 10 | pick name, price`.
+    Pipeline {
+        span: Span,
+        source: Box<Self>,
+        stages: Vec<Stage>,
+    },
+    /// Exclusion of `-term`, `!field:value` or `NOT field:value`.
+    Not {
+        span: Span,
+        argument: Box<Self>,
+    },
+    /// `[a, b]`, like the list of `status in ["open", "pending"]`.
+    ArrayExpression {
+        span: Span,
+        elements: Vec<Self>,
+    },
+    /// `{ name, price: $.pricing.amount }`, a new object built from the
+    /// document.
+    ObjectExpression {
+        span: Span,
+        properties: Vec<(String, Self)>,
+    },
+    /// `` `${brand} ${model}` ``, there is one more text in `quasis` than
+    /// `expressions`, interleaved starting with a text.
+    TemplateLiteral {
+        span: Span,
+        quasis: Vec<String>,
+        expressions: Vec<Self>,
+    },
+    /// Bounds of `price:10..20`, both inclusive. A `<` next to the `..`
+    /// excludes that bound, `10<..20`, `10..<20` or `10<..<20`. A missing
+    /// bound, written `*`, leaves the range open on that side.
+    RangeExpression {
+        span: Span,
+        start: Option<Box<Self>>,
+        end: Option<Box<Self>>,
+        start_exclusive: bool,
+        end_exclusive: bool,
+    },
+    /// Call of a function of the registry, like `starts_with(sku, "AB")`.
+    Call {
+        span: Span,
+        callee: String,
+        arguments: Vec<Self>,
+    },
+    /// `count()`, `sum(price)`, `avg`, `min`, `max` or `distinct_count` of
+    /// the documents of a group.
+    Aggregate {
+        span: Span,
+        function: String,
+        argument: Option<Box<Self>>,
+    },
+    /// `any`, `all` or `none` over the elements of a collection.
+    ///
+    /// - `any(tags, t => t = "red")`: explicit collection with a binding.
+    /// - `any(variants, variants.stock > 0)`: explicit collection, paths of the
+    ///   predicate starting with `variants` resolve against each element.
+    /// - `all(variants.stock > 0)`: implicit collection, taken from the first
+    ///   array found walking the leftmost path of the predicate.
+    Quantifier {
+        span: Span,
+        quantifier: String,
+        collection: Option<Box<Self>>,
+        binding: Option<String>,
+        predicate: Box<Self>,
+    },
 }
 
 impl Statement {
@@ -35,7 +161,85 @@ impl Statement {
             Self::Identifier { span, .. } => span,
             Self::MemberExpression { span, .. } => span,
             Self::Expression { span, .. } => span,
+            Self::Literal { span, .. } => span,
+            Self::Text { span, .. } => span,
+            Self::Wildcard { span, .. } => span,
+            Self::Fuzzy { span, .. } => span,
+            Self::Phrase { span, .. } => span,
+            Self::Boost { span, .. } => span,
+            Self::BinaryExpression { span, .. } => span,
+            Self::Select { span, .. } => span,
+            Self::Pipeline { span, .. } => span,
+            Self::Not { span, .. } => span,
+            Self::ArrayExpression { span, .. } => span,
+            Self::ObjectExpression { span, .. } => span,
+            Self::TemplateLiteral { span, .. } => span,
+            Self::RangeExpression { span, .. } => span,
+            Self::Call { span, .. } => span,
+            Self::Aggregate { span, .. } => span,
+            Self::Quantifier { span, .. } => span,
             // Self::IdentifierExpression { span, .. } => span,
         }
     }
+
+    /// Flatten an identifier or member expression into its path segments.
+    ///
+    /// `$.a["b"].c` is `["$", "a", "b", "c"]`.
+    pub fn to_path(&self) -> Option<Vec<String>> {
+        match self {
+            Self::Identifier {
+                value: Value::String(segment),
+                ..
+            } => Some(vec![segment.clone()]),
+            Self::MemberExpression {
+                object, property, ..
+            } => {
+                let mut path = object.to_path()?;
+                path.extend(property.to_path()?);
+                Some(path)
+            }
+            _ => None,
+        }
+    }
+
+    /// Name of a projection without alias, the last segment of a path, or
+    /// the function and the path of its argument, `avg(price)` is `avg_price`.
+    pub fn default_name(&self) -> Option<String> {
+        match self {
+            Self::Aggregate {
+                function, argument, ..
+            } => match argument {
+                Some(argument) => Some(format!("{}_{}", function, argument.default_name()?)),
+                None => Some(function.clone()),
+            },
+            Self::Call {
+                callee, arguments, ..
+            } => {
+                let mut segments = vec![callee.clone()];
+                for argument in arguments {
+                    segments.push(argument.default_name()?);
+                }
+                Some(segments.join("_"))
+            }
+            _ => self.to_path()?.pop(),
+        }
+    }
+
+    /// First path found walking the statement from the left, which is the
+    /// implicit collection of `all(variants.stock > 0)`.
+    pub fn leftmost_path(&self) -> Option<Vec<String>> {
+        match self {
+            Self::Identifier { .. } | Self::MemberExpression { .. } => self.to_path(),
+            Self::BinaryExpression { left, right, .. } => {
+                left.leftmost_path().or_else(|| right.leftmost_path())
+            }
+            Self::Expression { expresssion, .. } => expresssion
+                .iter()
+                .find_map(|statement| statement.leftmost_path()),
+            Self::Call { arguments, .. } => arguments
+                .iter()
+                .find_map(|statement| statement.leftmost_path()),
+            _ => None,
+        }
+    }
 }
@@ -1,6 +1,11 @@
 #[derive(Debug, Clone)]
 pub enum Value {
     String(String),
+    Number(f64),
+    Boolean(bool),
+    Null,
+    /// Pattern and flags of `/^ab.*z$/i`.
+    Regex(String, String),
 }
 
 impl Value {
@@ -8,3 +13,22 @@ impl Value {
         Self::String(val.to_string())
     }
 }
+
+impl From<&Value> for serde_json::Value {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::String(s) => serde_json::Value::String(s.clone()),
+            Value::Number(n) if n.fract() == 0.0 && n.abs() < i64::MAX as f64 => {
+                serde_json::Value::from(*n as i64)
+            }
+            Value::Number(n) => serde_json::Number::from_f64(*n)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null),
+            Value::Boolean(b) => serde_json::Value::Bool(*b),
+            Value::Null => serde_json::Value::Null,
+            Value::Regex(pattern, flags) => {
+                serde_json::Value::String(format!("/{}/{}", pattern, flags))
+            }
+        }
+    }
+}
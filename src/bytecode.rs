@@ -0,0 +1,25 @@
+//! Queries lowered into a compact stack-based bytecode run by a small
+//! virtual machine.
+//!
+//! A [`Program`] is the instructions of a query with tables of its
+//! constants, field paths, spans and terms, free text lowered for each
+//! default field with its terms analyzed and regexes compiled once. It is
+//! encoded into bytes to be cached and decoded back, and a [`Vm`] with the
+//! same analyzers runs it against documents with the results of
+//! [`crate::Evaluator::matches`]. `AND` and `OR` jump over their right side
+//! once the left one decides, and quantifiers jump back to their predicate
+//! for each element.
+//!
+//! ```text
+//! brand:acme price > 10      LoadField(0) PushConst(0) Cmp(Equal) JumpIfFalse(8)
+//!                            Pop LoadField(1) PushConst(1) Cmp(Greater)
+//! ```
+
+mod compiler;
+mod instruction;
+mod program;
+mod vm;
+
+pub use self::instruction::{Comparison, Instruction, Quantifier};
+pub use self::program::Program;
+pub use self::vm::Vm;
@@ -0,0 +1,366 @@
+use super::{Comparison, Instruction, Program, Quantifier};
+use crate::ast::statement::Statement;
+use crate::ast::value::Value as AstValue;
+use crate::evaluator::{compile_regex, strip_root, EvaluatorError, EvaluatorOptions, Leaf};
+use crate::tokenizer::token::Span;
+use serde_json::Value;
+
+/// Lowers statements into the instructions of a program, sharing equal
+/// constants, paths, spans and leaves.
+pub(super) struct Compiler<'a> {
+    program: Program,
+    options: &'a EvaluatorOptions,
+}
+
+impl<'a> Compiler<'a> {
+    pub(super) fn new(options: &'a EvaluatorOptions) -> Self {
+        Self {
+            program: Program::default(),
+            options,
+        }
+    }
+
+    pub(super) fn finish(self) -> Program {
+        self.program
+    }
+
+    pub(super) fn statement(&mut self, statement: &Statement) -> Result<(), EvaluatorError> {
+        match statement {
+            Statement::Expression { expresssion, .. } => {
+                if expresssion.is_empty() {
+                    let constant = self.constant(Value::Bool(true));
+                    self.emit(Instruction::PushConst(constant));
+                    return Ok(());
+                }
+                let mut jumps = vec![];
+                for (position, condition) in expresssion.iter().enumerate() {
+                    if position > 0 {
+                        self.emit(Instruction::Pop);
+                    }
+                    self.statement(condition)?;
+                    self.ensure_bool();
+                    if position + 1 < expresssion.len() {
+                        jumps.push(self.emit(Instruction::JumpIfFalse(0)));
+                    }
+                }
+                self.patch(jumps);
+            }
+            Statement::Identifier { .. } | Statement::MemberExpression { .. } => {
+                let path = self.path(statement.to_path().unwrap_or_default());
+                self.emit(Instruction::LoadField(path));
+            }
+            Statement::Literal { value, .. } => {
+                let constant = self.constant(value.into());
+                self.emit(Instruction::PushConst(constant));
+            }
+            Statement::Not { argument, .. } => {
+                self.statement(argument)?;
+                self.emit(Instruction::Not);
+            }
+            Statement::Boost { argument, .. } => self.statement(argument)?,
+            Statement::ArrayExpression { elements, .. } => {
+                for element in elements {
+                    self.statement(element)?;
+                }
+                self.emit(Instruction::MakeArray(elements.len()));
+            }
+            Statement::ObjectExpression { properties, .. } => {
+                for (_, value) in properties {
+                    self.statement(value)?;
+                }
+                let keys = properties
+                    .iter()
+                    .map(|(key, _)| Value::String(key.clone()))
+                    .collect();
+                let keys = self.constant(Value::Array(keys));
+                self.emit(Instruction::MakeObject(keys));
+            }
+            Statement::TemplateLiteral {
+                quasis,
+                expressions,
+                ..
+            } => {
+                for expression in expressions {
+                    self.statement(expression)?;
+                }
+                let quasis = quasis.iter().map(|quasi| Value::String(quasi.clone()));
+                let quasis = self.constant(Value::Array(quasis.collect()));
+                self.emit(Instruction::Template(quasis));
+            }
+            Statement::Call {
+                span,
+                callee,
+                arguments,
+            } => {
+                for argument in arguments {
+                    self.statement(argument)?;
+                }
+                let function = self.constant(Value::String(callee.clone()));
+                let span = self.span(span);
+                self.emit(Instruction::Call {
+                    function,
+                    arity: arguments.len(),
+                    span,
+                });
+            }
+            Statement::RangeExpression { span, .. }
+            | Statement::Select { span, .. }
+            | Statement::Pipeline { span, .. }
+            | Statement::Aggregate { span, .. } => {
+                return Err(EvaluatorError::StatementNotSupported(span.clone()))
+            }
+            Statement::Text { .. }
+            | Statement::Wildcard { .. }
+            | Statement::Fuzzy { .. }
+            | Statement::Phrase { .. } => {
+                // Any default field, the leaf prepared for its analyzer.
+                let fields = self.options.default_field_paths();
+                if fields.is_empty() {
+                    let constant = self.constant(Value::Bool(false));
+                    self.emit(Instruction::PushConst(constant));
+                }
+                let mut jumps = vec![];
+                for (position, (path, analyzer)) in fields.iter().enumerate() {
+                    if position > 0 {
+                        self.emit(Instruction::Pop);
+                    }
+                    let leaf = Leaf::free_text(statement, analyzer.as_ref()).unwrap();
+                    let field = self.constant(Value::String(path.join(".")));
+                    let path = self.path(path.clone());
+                    let leaf = self.leaf(leaf);
+                    self.emit(Instruction::LoadField(path));
+                    self.emit(Instruction::Match {
+                        leaf,
+                        field: Some(field),
+                    });
+                    if position + 1 < fields.len() {
+                        jumps.push(self.emit(Instruction::JumpIfTrue(0)));
+                    }
+                }
+                self.patch(jumps);
+            }
+            Statement::BinaryExpression {
+                span,
+                left,
+                operator,
+                right,
+            } => self.binary(span, left, operator, right)?,
+            Statement::Quantifier {
+                span,
+                quantifier,
+                collection,
+                binding,
+                predicate,
+            } => {
+                let quantifier = match quantifier.as_str() {
+                    "any" => Quantifier::Any,
+                    "all" => Quantifier::All,
+                    _ => Quantifier::None,
+                };
+                let iterate = match collection {
+                    Some(collection) => {
+                        let prefix = match binding {
+                            Some(binding) => vec![binding.clone()],
+                            None => strip_root(collection.to_path().unwrap_or_default()),
+                        };
+                        self.statement(collection)?;
+                        let prefix = self.path(prefix);
+                        self.emit(Instruction::Iterate {
+                            quantifier,
+                            prefix,
+                            end: 0,
+                        })
+                    }
+                    None => {
+                        let path = predicate.leftmost_path().map(strip_root).ok_or_else(|| {
+                            EvaluatorError::QuantifierWithoutCollection(span.clone())
+                        })?;
+                        let path = self.path(path);
+                        self.emit(Instruction::IterateImplicit {
+                            quantifier,
+                            path,
+                            end: 0,
+                        })
+                    }
+                };
+                let start = self.program.instructions.len();
+                self.statement(predicate)?;
+                self.emit(Instruction::Next(start));
+                self.patch(vec![iterate]);
+            }
+        }
+        Ok(())
+    }
+
+    fn binary(
+        &mut self,
+        span: &Span,
+        left: &Statement,
+        operator: &str,
+        right: &Statement,
+    ) -> Result<(), EvaluatorError> {
+        match (operator, right) {
+            ("&&" | "||", right) => {
+                self.statement(left)?;
+                self.ensure_bool();
+                let jump = match operator {
+                    "&&" => self.emit(Instruction::JumpIfFalse(0)),
+                    _ => self.emit(Instruction::JumpIfTrue(0)),
+                };
+                self.emit(Instruction::Pop);
+                self.statement(right)?;
+                self.ensure_bool();
+                self.patch(vec![jump]);
+            }
+            (
+                "=" | ":",
+                Statement::Wildcard { .. } | Statement::Fuzzy { .. } | Statement::Phrase { .. },
+            ) => {
+                self.statement(left)?;
+                let analyzer = self.options.statement_analyzer(left);
+                let leaf = Leaf::field(right, analyzer.as_ref()).unwrap();
+                let field = left
+                    .to_path()
+                    .map(|path| self.constant(Value::String(strip_root(path).join("."))));
+                let leaf = self.leaf(leaf);
+                self.emit(Instruction::Match { leaf, field });
+            }
+            (
+                "=" | ":",
+                Statement::RangeExpression {
+                    start,
+                    end,
+                    start_exclusive,
+                    end_exclusive,
+                    ..
+                },
+            ) => {
+                self.statement(left)?;
+                for bound in start.iter().chain(end) {
+                    self.statement(bound)?;
+                }
+                self.emit(Instruction::InRange {
+                    start: start.is_some(),
+                    end: end.is_some(),
+                    start_exclusive: *start_exclusive,
+                    end_exclusive: *end_exclusive,
+                });
+            }
+            ("=~" | "!~", right) => {
+                let negate = operator == "!~";
+                let span = self.span(&right.clone().to_span());
+                // A literal pattern, a regex or a string, is compiled with
+                // the program.
+                let literal = match right {
+                    Statement::Literal {
+                        value: AstValue::Regex(pattern, flags),
+                        span,
+                    } => Some((pattern, flags.as_str(), span)),
+                    Statement::Literal {
+                        value: AstValue::String(pattern),
+                        span,
+                    } => Some((pattern, "", span)),
+                    _ => None,
+                };
+                match literal {
+                    Some((pattern, flags, regex_span)) => {
+                        compile_regex(pattern, flags).map_err(|message| {
+                            EvaluatorError::InvalidRegex(message, regex_span.clone())
+                        })?;
+                        self.statement(left)?;
+                        let regex = self.constant(Value::Array(vec![
+                            Value::String(pattern.clone()),
+                            Value::String(flags.to_string()),
+                        ]));
+                        self.emit(Instruction::MatchRegex {
+                            regex,
+                            negate,
+                            span,
+                        });
+                    }
+                    None => {
+                        self.statement(right)?;
+                        self.statement(left)?;
+                        self.emit(Instruction::MatchPattern { negate, span });
+                    }
+                }
+            }
+            (operator, right) => {
+                let comparison = Comparison::from_operator(operator).ok_or_else(|| {
+                    EvaluatorError::OperatorNotSupported(operator.to_string(), span.clone())
+                })?;
+                self.statement(left)?;
+                self.statement(right)?;
+                self.emit(Instruction::Cmp(comparison));
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an instruction and return its position.
+    fn emit(&mut self, instruction: Instruction) -> usize {
+        self.program.instructions.push(instruction);
+        self.program.instructions.len() - 1
+    }
+
+    /// Make the value on top a boolean, unless the last instruction pushed
+    /// one. Jumps to the next instruction always carry a boolean.
+    fn ensure_bool(&mut self) {
+        let boolean = matches!(
+            self.program.instructions.last(),
+            Some(
+                Instruction::Cmp(_)
+                    | Instruction::InRange { .. }
+                    | Instruction::ToBool
+                    | Instruction::Not
+                    | Instruction::MatchRegex { .. }
+                    | Instruction::MatchPattern { .. }
+                    | Instruction::Match { .. }
+                    | Instruction::Next(_)
+            )
+        );
+        if !boolean {
+            self.emit(Instruction::ToBool);
+        }
+    }
+
+    /// Point the jumps at the next instruction.
+    fn patch(&mut self, jumps: Vec<usize>) {
+        let target = self.program.instructions.len();
+        for jump in jumps {
+            match &mut self.program.instructions[jump] {
+                Instruction::JumpIfFalse(to) | Instruction::JumpIfTrue(to) => *to = target,
+                Instruction::Iterate { end, .. } | Instruction::IterateImplicit { end, .. } => {
+                    *end = target
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn constant(&mut self, value: Value) -> usize {
+        intern(&mut self.program.constants, value)
+    }
+
+    fn leaf(&mut self, leaf: Leaf) -> usize {
+        intern(&mut self.program.leaves, leaf)
+    }
+
+    fn path(&mut self, path: Vec<String>) -> usize {
+        intern(&mut self.program.paths, strip_root(path))
+    }
+
+    fn span(&mut self, span: &Span) -> usize {
+        intern(&mut self.program.spans, span.clone())
+    }
+}
+
+fn intern<T: PartialEq>(table: &mut Vec<T>, value: T) -> usize {
+    match table.iter().position(|other| *other == value) {
+        Some(position) => position,
+        None => {
+            table.push(value);
+            table.len() - 1
+        }
+    }
+}
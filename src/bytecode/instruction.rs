@@ -0,0 +1,132 @@
+/// Operation of the [`super::Vm`], its operands index the tables of the
+/// [`super::Program`] or are positions of its instructions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    /// Push the value of a path, rebound by the enclosing quantifiers.
+    LoadField(usize),
+    /// Push a constant.
+    PushConst(usize),
+    /// Pop the right and left values and push the comparison.
+    Cmp(Comparison),
+    /// Pop the bounds present and the value and push if it is within them.
+    InRange {
+        start: bool,
+        end: bool,
+        start_exclusive: bool,
+        end_exclusive: bool,
+    },
+    /// Pop a value and push whether it is truthy.
+    ToBool,
+    /// Pop a value and push whether it is falsy.
+    Not,
+    Pop,
+    /// Jump if the value on top is falsy, leaving it on the stack.
+    JumpIfFalse(usize),
+    /// Jump if the value on top is truthy, leaving it on the stack.
+    JumpIfTrue(usize),
+    /// Pop that many values and push them as an array.
+    MakeArray(usize),
+    /// Pop a value for each key of a constant array of keys and push the
+    /// object.
+    MakeObject(usize),
+    /// Pop the values inserted between the strings of a constant array and
+    /// push the text.
+    Template(usize),
+    /// Pop the arguments and push the result of the function named by a
+    /// constant.
+    Call {
+        function: usize,
+        arity: usize,
+        span: usize,
+    },
+    /// Pop a value and push whether a string matches the regex of a
+    /// constant `[pattern, flags]`, compiled with the program.
+    MatchRegex {
+        regex: usize,
+        negate: bool,
+        span: usize,
+    },
+    /// Pop a value and a pattern and push whether a string matches it.
+    MatchPattern {
+        negate: bool,
+        span: usize,
+    },
+    /// Pop a value and push whether it matches a leaf of the program, a
+    /// term prepared for the analyzer of the field named by a constant or
+    /// the default analyzer.
+    Match {
+        leaf: usize,
+        field: Option<usize>,
+    },
+    /// Pop a collection and bind its first element to a path, or push the
+    /// result for no elements and jump to `end`.
+    Iterate {
+        quantifier: Quantifier,
+        prefix: usize,
+        end: usize,
+    },
+    /// Like [`Instruction::Iterate`] with the first array of a path, see
+    /// [`crate::evaluator`].
+    IterateImplicit {
+        quantifier: Quantifier,
+        path: usize,
+        end: usize,
+    },
+    /// Pop the result of the predicate for the bound element, then jump back
+    /// to the predicate with the next element or push the result of the
+    /// quantifier.
+    Next(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    Greater,
+    Less,
+    GreaterOrEqual,
+    LessOrEqual,
+    In,
+}
+
+impl Comparison {
+    pub fn from_operator(operator: &str) -> Option<Self> {
+        match operator {
+            "=" | ":" => Some(Self::Equal),
+            ">" => Some(Self::Greater),
+            "<" => Some(Self::Less),
+            ">=" => Some(Self::GreaterOrEqual),
+            "<=" => Some(Self::LessOrEqual),
+            "in" => Some(Self::In),
+            _ => None,
+        }
+    }
+
+    pub fn operator(&self) -> &'static str {
+        match self {
+            Self::Equal => "=",
+            Self::Greater => ">",
+            Self::Less => "<",
+            Self::GreaterOrEqual => ">=",
+            Self::LessOrEqual => "<=",
+            Self::In => "in",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quantifier {
+    Any,
+    All,
+    None,
+}
+
+impl Quantifier {
+    /// Result for the number of elements satisfying the predicate.
+    pub fn result(&self, satisfied: usize, count: usize) -> bool {
+        match self {
+            Self::Any => satisfied > 0,
+            Self::All => satisfied == count,
+            Self::None => satisfied == 0,
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use super::compiler::Compiler;
+use super::{Comparison, Instruction, Quantifier};
+use crate::ast::statement::Statement;
+use crate::ast::AST;
+use crate::evaluator::{compile_regex, EvaluatorError, EvaluatorOptions, Leaf};
+use crate::tokenizer::token::Span;
+use regex::Regex;
+use serde_json::Value;
+
+/// First bytes of an encoded program, with the version of the encoding.
+const MAGIC: &[u8; 4] = b"QLB2";
+
+/// A query lowered into instructions for the [`super::Vm`].
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+    pub constants: Vec<Value>,
+    /// Paths of fields, split and without the `$` root.
+    pub paths: Vec<Vec<String>>,
+    /// Positions in the query of the nodes that may fail when evaluated.
+    pub spans: Vec<Span>,
+    /// Terms prepared for the analyzer of their field.
+    pub(super) leaves: Vec<Leaf>,
+    /// Regex of each `[pattern, flags]` constant of a `MatchRegex`, compiled
+    /// once with the program.
+    pub(super) regexes: Vec<Option<Regex>>,
+}
+
+impl Program {
+    /// Lower a query with the default [`EvaluatorOptions`].
+    pub fn compile(ast: &AST) -> Result<Self, EvaluatorError> {
+        Self::compile_with_options(ast, &EvaluatorOptions::default())
+    }
+
+    /// Lower a query, the stages of a pipeline are ignored.
+    ///
+    /// Free text is looked up in the default fields of `options`, and its
+    /// terms normalized and phrases analyzed once with the analyzer of each
+    /// field, so the [`super::Vm`] running the program needs the same
+    /// analyzers. A statement or operator the evaluator doesn't support, an
+    /// invalid regex literal or pattern string or a quantifier without
+    /// collection fail here rather than when the node is evaluated.
+    /// Functions are looked up by the [`super::Vm`].
+    pub fn compile_with_options(
+        ast: &AST,
+        options: &EvaluatorOptions,
+    ) -> Result<Self, EvaluatorError> {
+        let statement = match ast.statement() {
+            Statement::Pipeline { source, .. } => source,
+            statement => statement,
+        };
+        let mut compiler = Compiler::new(options);
+        compiler.statement(statement)?;
+        let mut program = compiler.finish();
+        program.compile_regexes()?;
+        Ok(program)
+    }
+
+    /// Bytes of the program: the tables and then the instructions, numbers
+    /// as LEB128 and constants as JSON.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        write(&mut bytes, self.constants.len());
+        for constant in &self.constants {
+            write_str(&mut bytes, &constant.to_string());
+        }
+        write(&mut bytes, self.paths.len());
+        for path in &self.paths {
+            write(&mut bytes, path.len());
+            for segment in path {
+                write_str(&mut bytes, segment);
+            }
+        }
+        write(&mut bytes, self.spans.len());
+        for span in &self.spans {
+            write(&mut bytes, span.start);
+            write(&mut bytes, span.end);
+        }
+        write(&mut bytes, self.leaves.len());
+        for leaf in &self.leaves {
+            encode_leaf(&mut bytes, leaf);
+        }
+        write(&mut bytes, self.instructions.len());
+        for instruction in &self.instructions {
+            encode_instruction(&mut bytes, instruction);
+        }
+        bytes
+    }
+
+    /// Read a program written by [`Program::encode`], checking every operand
+    /// is within the tables.
+    pub fn decode(bytes: &[u8]) -> Result<Self, EvaluatorError> {
+        let mut reader = Reader { bytes, position: 0 };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid("not a program"));
+        }
+        let mut program = Program::default();
+        for _ in 0..reader.read()? {
+            let constant = serde_json::from_str(&reader.read_str()?)
+                .map_err(|error| invalid(&error.to_string()))?;
+            program.constants.push(constant);
+        }
+        for _ in 0..reader.read()? {
+            let mut path = vec![];
+            for _ in 0..reader.read()? {
+                path.push(reader.read_str()?);
+            }
+            program.paths.push(path);
+        }
+        for _ in 0..reader.read()? {
+            let (start, end) = (reader.read()?, reader.read()?);
+            program.spans.push(Span { start, end });
+        }
+        for _ in 0..reader.read()? {
+            program.leaves.push(decode_leaf(&mut reader)?);
+        }
+        for _ in 0..reader.read()? {
+            let instruction = decode_instruction(&mut reader)?;
+            program.instructions.push(instruction);
+        }
+        if reader.position != bytes.len() {
+            return Err(invalid("trailing bytes"));
+        }
+        program.check()?;
+        program.compile_regexes()?;
+        Ok(program)
+    }
+
+    fn compile_regexes(&mut self) -> Result<(), EvaluatorError> {
+        self.regexes = vec![None; self.constants.len()];
+        for instruction in &self.instructions {
+            if let Instruction::MatchRegex { regex, span, .. } = *instruction {
+                let (pattern, flags) = (&self.constants[regex][0], &self.constants[regex][1]);
+                let compiled = compile_regex(pattern.as_str().unwrap(), flags.as_str().unwrap())
+                    .map_err(|message| {
+                        EvaluatorError::InvalidRegex(message, self.spans[span].clone())
+                    })?;
+                self.regexes[regex] = Some(compiled);
+            }
+        }
+        Ok(())
+    }
+
+    fn check(&self) -> Result<(), EvaluatorError> {
+        let length = self.instructions.len();
+        let constant = |index: usize| index < self.constants.len();
+        let span = |index: usize| index < self.spans.len();
+        let strings = |index: usize| {
+            self.constants.get(index).is_some_and(|value| match value {
+                Value::Array(items) => items.iter().all(Value::is_string),
+                _ => false,
+            })
+        };
+        // Jumps only go forward, see `check_loops` for the jumps back.
+        for (position, instruction) in self.instructions.iter().enumerate() {
+            let forward = |to: usize| position < to && to <= length;
+            let valid = match *instruction {
+                Instruction::LoadField(path) => path < self.paths.len(),
+                Instruction::PushConst(index) => constant(index),
+                Instruction::MakeObject(keys) => strings(keys),
+                Instruction::Template(quasis) => strings(quasis),
+                Instruction::JumpIfFalse(to) | Instruction::JumpIfTrue(to) => forward(to),
+                Instruction::Call {
+                    function, span: s, ..
+                } => self.constants.get(function).is_some_and(Value::is_string) && span(s),
+                Instruction::MatchRegex { regex, span: s, .. } => {
+                    strings(regex)
+                        && self.constants[regex].as_array().map(Vec::len) == Some(2)
+                        && span(s)
+                }
+                Instruction::MatchPattern { span: s, .. } => span(s),
+                Instruction::Match { leaf, field } => {
+                    leaf < self.leaves.len()
+                        && field.is_none_or(|field| {
+                            self.constants.get(field).is_some_and(Value::is_string)
+                        })
+                }
+                Instruction::Iterate { prefix, end, .. } => {
+                    prefix < self.paths.len() && forward(end)
+                }
+                Instruction::IterateImplicit { path, end, .. } => {
+                    self.paths.get(path).is_some_and(|path| !path.is_empty()) && forward(end)
+                }
+                Instruction::Cmp(_)
+                | Instruction::InRange { .. }
+                | Instruction::ToBool
+                | Instruction::Not
+                | Instruction::Pop
+                | Instruction::MakeArray(_)
+                | Instruction::Next(_) => true,
+            };
+            if !valid {
+                return Err(invalid(&format!("invalid operand {:?}", instruction)));
+            }
+        }
+        self.check_loops()
+    }
+
+    /// Check the loops of the quantifiers end: the instruction before the
+    /// `end` of each iteration is the `Next` jumping back right after it,
+    /// loops nest within each other and jumps stay within their loop.
+    fn check_loops(&self) -> Result<(), EvaluatorError> {
+        // The innermost loop of each position, by the position of its `Next`.
+        let mut loops: Vec<usize> = vec![];
+        let mut owners = vec![None; self.instructions.len() + 1];
+        for (position, instruction) in self.instructions.iter().enumerate() {
+            owners[position] = loops.last().copied();
+            match *instruction {
+                Instruction::Iterate { end, .. } | Instruction::IterateImplicit { end, .. } => {
+                    let next = end - 1;
+                    let nested = loops.last().is_none_or(|outer| next < *outer);
+                    if !nested || self.instructions[next] != Instruction::Next(position + 1) {
+                        return Err(invalid(&format!("unmatched {:?}", instruction)));
+                    }
+                    loops.push(next);
+                }
+                Instruction::Next(_) if loops.pop() != Some(position) => {
+                    return Err(invalid(&format!("unmatched {:?}", instruction)));
+                }
+                _ => {}
+            }
+        }
+        for (position, instruction) in self.instructions.iter().enumerate() {
+            if let Instruction::JumpIfFalse(to) | Instruction::JumpIfTrue(to) = *instruction {
+                if owners[to] != owners[position] {
+                    return Err(invalid(&format!("jump out of its loop {:?}", instruction)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Programs are equal when their tables are, the regexes being compiled
+/// from the constants.
+impl PartialEq for Program {
+    fn eq(&self, other: &Self) -> bool {
+        self.instructions == other.instructions
+            && self.constants == other.constants
+            && self.paths == other.paths
+            && self.spans == other.spans
+            && self.leaves == other.leaves
+    }
+}
+
+fn invalid(reason: &str) -> EvaluatorError {
+    EvaluatorError::InvalidProgram(reason.to_string())
+}
+
+fn encode_instruction(bytes: &mut Vec<u8>, instruction: &Instruction) {
+    let (opcode, operands): (u8, Vec<usize>) = match *instruction {
+        Instruction::LoadField(path) => (0, vec![path]),
+        Instruction::PushConst(index) => (1, vec![index]),
+        Instruction::Cmp(comparison) => (2, vec![comparison as usize]),
+        Instruction::InRange {
+            start,
+            end,
+            start_exclusive,
+            end_exclusive,
+        } => (
+            3,
+            vec![
+                start as usize,
+                end as usize,
+                start_exclusive as usize,
+                end_exclusive as usize,
+            ],
+        ),
+        Instruction::ToBool => (4, vec![]),
+        Instruction::Not => (5, vec![]),
+        Instruction::Pop => (6, vec![]),
+        Instruction::JumpIfFalse(to) => (7, vec![to]),
+        Instruction::JumpIfTrue(to) => (8, vec![to]),
+        Instruction::MakeArray(length) => (9, vec![length]),
+        Instruction::MakeObject(keys) => (10, vec![keys]),
+        Instruction::Template(quasis) => (11, vec![quasis]),
+        Instruction::Call {
+            function,
+            arity,
+            span,
+        } => (12, vec![function, arity, span]),
+        Instruction::MatchRegex {
+            regex,
+            negate,
+            span,
+        } => (13, vec![regex, negate as usize, span]),
+        Instruction::MatchPattern { negate, span } => (14, vec![negate as usize, span]),
+        Instruction::Match { leaf, field } => (15, vec![leaf, field.map_or(0, |field| field + 1)]),
+        Instruction::Iterate {
+            quantifier,
+            prefix,
+            end,
+        } => (16, vec![quantifier as usize, prefix, end]),
+        Instruction::IterateImplicit {
+            quantifier,
+            path,
+            end,
+        } => (17, vec![quantifier as usize, path, end]),
+        Instruction::Next(start) => (18, vec![start]),
+    };
+    bytes.push(opcode);
+    for operand in operands {
+        write(bytes, operand);
+    }
+}
+
+fn decode_instruction(reader: &mut Reader) -> Result<Instruction, EvaluatorError> {
+    let opcode = reader.take(1)?[0];
+    let instruction = match opcode {
+        0 => Instruction::LoadField(reader.read()?),
+        1 => Instruction::PushConst(reader.read()?),
+        2 => Instruction::Cmp(match reader.read()? {
+            0 => Comparison::Equal,
+            1 => Comparison::Greater,
+            2 => Comparison::Less,
+            3 => Comparison::GreaterOrEqual,
+            4 => Comparison::LessOrEqual,
+            5 => Comparison::In,
+            _ => return Err(invalid("unknown comparison")),
+        }),
+        3 => Instruction::InRange {
+            start: reader.read_bool()?,
+            end: reader.read_bool()?,
+            start_exclusive: reader.read_bool()?,
+            end_exclusive: reader.read_bool()?,
+        },
+        4 => Instruction::ToBool,
+        5 => Instruction::Not,
+        6 => Instruction::Pop,
+        7 => Instruction::JumpIfFalse(reader.read()?),
+        8 => Instruction::JumpIfTrue(reader.read()?),
+        9 => Instruction::MakeArray(reader.read()?),
+        10 => Instruction::MakeObject(reader.read()?),
+        11 => Instruction::Template(reader.read()?),
+        12 => Instruction::Call {
+            function: reader.read()?,
+            arity: reader.read()?,
+            span: reader.read()?,
+        },
+        13 => Instruction::MatchRegex {
+            regex: reader.read()?,
+            negate: reader.read_bool()?,
+            span: reader.read()?,
+        },
+        14 => Instruction::MatchPattern {
+            negate: reader.read_bool()?,
+            span: reader.read()?,
+        },
+        15 => Instruction::Match {
+            leaf: reader.read()?,
+            field: reader.read()?.checked_sub(1),
+        },
+        16 => Instruction::Iterate {
+            quantifier: reader.read_quantifier()?,
+            prefix: reader.read()?,
+            end: reader.read()?,
+        },
+        17 => Instruction::IterateImplicit {
+            quantifier: reader.read_quantifier()?,
+            path: reader.read()?,
+            end: reader.read()?,
+        },
+        18 => Instruction::Next(reader.read()?),
+        _ => return Err(invalid(&format!("unknown opcode {}", opcode))),
+    };
+    Ok(instruction)
+}
+
+/// Write a leaf as its kind, its terms and its distance or slop.
+fn encode_leaf(bytes: &mut Vec<u8>, leaf: &Leaf) {
+    let (kind, terms, number) = match leaf {
+        Leaf::Text(text) => (0, std::slice::from_ref(text), 0),
+        Leaf::Wildcard(pattern) => (1, std::slice::from_ref(pattern), 0),
+        Leaf::Fuzzy(term, distance) => (2, std::slice::from_ref(term), *distance),
+        Leaf::Phrase(phrase, slop) => (3, phrase.as_slice(), *slop),
+        Leaf::Glob(pattern) => (4, std::slice::from_ref(pattern), 0),
+        Leaf::FuzzyString(term, distance) => (5, std::slice::from_ref(term), *distance),
+    };
+    bytes.push(kind);
+    write(bytes, terms.len());
+    for term in terms {
+        write_str(bytes, term);
+    }
+    write(bytes, number);
+}
+
+fn decode_leaf(reader: &mut Reader) -> Result<Leaf, EvaluatorError> {
+    let kind = reader.take(1)?[0];
+    let mut terms = vec![];
+    for _ in 0..reader.read()? {
+        terms.push(reader.read_str()?);
+    }
+    let number = reader.read()?;
+    if kind == 3 {
+        return Ok(Leaf::Phrase(terms, number));
+    }
+    let [term] = <[String; 1]>::try_from(terms).map_err(|_| invalid("invalid leaf"))?;
+    match kind {
+        0 => Ok(Leaf::Text(term)),
+        1 => Ok(Leaf::Wildcard(term)),
+        2 => Ok(Leaf::Fuzzy(term, number)),
+        4 => Ok(Leaf::Glob(term)),
+        5 => Ok(Leaf::FuzzyString(term, number)),
+        _ => Err(invalid(&format!("unknown leaf {}", kind))),
+    }
+}
+
+/// Write a number as unsigned LEB128, 7 bits per byte.
+fn write(bytes: &mut Vec<u8>, mut number: usize) {
+    loop {
+        let byte = (number & 0x7f) as u8;
+        number >>= 7;
+        if number == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_str(bytes: &mut Vec<u8>, text: &str) {
+    write(bytes, text.len());
+    bytes.extend_from_slice(text.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], EvaluatorError> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position.saturating_add(length))
+            .ok_or_else(|| invalid("unexpected end"))?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn read(&mut self) -> Result<usize, EvaluatorError> {
+        let mut number = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.take(1)?[0];
+            number |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(number);
+            }
+        }
+        Err(invalid("number too large"))
+    }
+
+    fn read_bool(&mut self) -> Result<bool, EvaluatorError> {
+        match self.read()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid("invalid boolean")),
+        }
+    }
+
+    fn read_quantifier(&mut self) -> Result<Quantifier, EvaluatorError> {
+        match self.read()? {
+            0 => Ok(Quantifier::Any),
+            1 => Ok(Quantifier::All),
+            2 => Ok(Quantifier::None),
+            _ => Err(invalid("unknown quantifier")),
+        }
+    }
+
+    fn read_str(&mut self) -> Result<String, EvaluatorError> {
+        let length = self.read()?;
+        let bytes = self.take(length)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("invalid UTF-8"))
+    }
+}
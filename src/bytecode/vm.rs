@@ -0,0 +1,284 @@
+use super::{Instruction, Program, Quantifier};
+use crate::evaluator::{
+    any_string, compare, elements, implicit_elements, in_range, is_truthy, lookup, range_bound,
+    to_string, EvaluatorError, EvaluatorOptions, RegexCache,
+};
+use serde_json::Value;
+
+/// Runs programs against documents with the functions and analyzers of the
+/// options, those the programs are compiled with.
+pub struct Vm {
+    options: EvaluatorOptions,
+    /// Regexes of the patterns only known for each document.
+    regexes: RegexCache,
+}
+
+/// Elements walked by a quantifier.
+struct Frame {
+    quantifier: Quantifier,
+    /// Position of the predicate its `Next` jumps back to.
+    start: usize,
+    /// Path of the program and the length of its prefix the element rebinds.
+    prefix: (usize, usize),
+    items: Vec<Value>,
+    index: usize,
+    satisfied: usize,
+}
+
+impl Vm {
+    pub fn new(options: &EvaluatorOptions) -> Self {
+        Self {
+            options: options.clone(),
+            regexes: RegexCache::default(),
+        }
+    }
+
+    /// Test if `document` matches the program.
+    pub fn matches(&self, program: &Program, document: &Value) -> Result<bool, EvaluatorError> {
+        let mut stack: Vec<Value> = Vec::with_capacity(16);
+        let mut frames: Vec<Frame> = vec![];
+        let mut position = 0;
+        while let Some(instruction) = program.instructions.get(position) {
+            position += 1;
+            match *instruction {
+                Instruction::LoadField(path) => {
+                    stack.push(resolve(program, &program.paths[path], document, &frames))
+                }
+                Instruction::PushConst(index) => stack.push(program.constants[index].clone()),
+                Instruction::Cmp(comparison) => {
+                    let right = pop(&mut stack)?;
+                    let left = pop(&mut stack)?;
+                    let result = compare(&left, comparison.operator(), &right);
+                    stack.push(Value::Bool(result));
+                }
+                Instruction::InRange {
+                    start,
+                    end,
+                    start_exclusive,
+                    end_exclusive,
+                } => {
+                    let end = if end { Some(pop(&mut stack)?) } else { None };
+                    let start = if start { Some(pop(&mut stack)?) } else { None };
+                    let value = pop(&mut stack)?;
+                    let result = in_range(
+                        &value,
+                        range_bound(start.as_ref(), start_exclusive),
+                        range_bound(end.as_ref(), end_exclusive),
+                    );
+                    stack.push(Value::Bool(result));
+                }
+                Instruction::ToBool => {
+                    let value = pop(&mut stack)?;
+                    stack.push(Value::Bool(is_truthy(&value)));
+                }
+                Instruction::Not => {
+                    let value = pop(&mut stack)?;
+                    stack.push(Value::Bool(!is_truthy(&value)));
+                }
+                Instruction::Pop => {
+                    pop(&mut stack)?;
+                }
+                Instruction::JumpIfFalse(to) => {
+                    if !is_truthy(stack.last().ok_or_else(underflow)?) {
+                        position = to;
+                    }
+                }
+                Instruction::JumpIfTrue(to) => {
+                    if is_truthy(stack.last().ok_or_else(underflow)?) {
+                        position = to;
+                    }
+                }
+                Instruction::MakeArray(length) => {
+                    let values = pop_many(&mut stack, length)?;
+                    stack.push(Value::Array(values));
+                }
+                Instruction::MakeObject(keys) => {
+                    let keys = program.constants[keys].as_array().unwrap();
+                    let values = pop_many(&mut stack, keys.len())?;
+                    let object = keys
+                        .iter()
+                        .map(|key| key.as_str().unwrap().to_string())
+                        .zip(values)
+                        .collect();
+                    stack.push(Value::Object(object));
+                }
+                Instruction::Template(quasis) => {
+                    let quasis = program.constants[quasis].as_array().unwrap();
+                    let values = pop_many(&mut stack, quasis.len().saturating_sub(1))?;
+                    let mut text = String::new();
+                    for (index, quasi) in quasis.iter().enumerate() {
+                        if index > 0 {
+                            text.push_str(&to_string(&values[index - 1]));
+                        }
+                        text.push_str(quasi.as_str().unwrap());
+                    }
+                    stack.push(Value::String(text));
+                }
+                Instruction::Call {
+                    function,
+                    arity,
+                    span,
+                } => {
+                    let name = program.constants[function].as_str().unwrap();
+                    let span = &program.spans[span];
+                    let function = self.options.functions.get(name).ok_or_else(|| {
+                        EvaluatorError::FunctionNotFound(name.to_string(), span.clone())
+                    })?;
+                    if function.signature.arity() != arity {
+                        return Err(EvaluatorError::FunctionArity(
+                            name.to_string(),
+                            span.clone(),
+                        ));
+                    }
+                    let arguments = pop_many(&mut stack, arity)?;
+                    stack.push(function.call(&arguments));
+                }
+                Instruction::MatchRegex { regex, negate, .. } => {
+                    let regex = program.regexes.get(regex).and_then(Option::as_ref);
+                    let regex = regex.ok_or_else(|| invalid("regex not compiled"))?;
+                    let value = pop(&mut stack)?;
+                    let matches = any_string(&value, |text| regex.is_match(text));
+                    stack.push(Value::Bool(matches != negate));
+                }
+                Instruction::MatchPattern { negate, span } => {
+                    let value = pop(&mut stack)?;
+                    let regex = match pop(&mut stack)? {
+                        Value::String(pattern) => self.regexes.get(&pattern, ""),
+                        _ => Err("pattern isn't a string".to_string()),
+                    };
+                    let regex = regex.map_err(|message| {
+                        EvaluatorError::InvalidRegex(message, program.spans[span].clone())
+                    })?;
+                    let matches = any_string(&value, |text| regex.is_match(text));
+                    stack.push(Value::Bool(matches != negate));
+                }
+                Instruction::Match { leaf, field } => {
+                    let analyzer = match field {
+                        Some(field) => self.options.analyzer(text(program, field)),
+                        None => self.options.default_analyzer.as_ref(),
+                    };
+                    let value = pop(&mut stack)?;
+                    let matches = program.leaves[leaf].matches(&value, analyzer);
+                    stack.push(Value::Bool(matches));
+                }
+                Instruction::Iterate {
+                    quantifier,
+                    prefix,
+                    end,
+                } => {
+                    let items = elements(pop(&mut stack)?);
+                    let length = program.paths[prefix].len();
+                    let frame = (quantifier, position, (prefix, length));
+                    if !iterate(&mut frames, frame, items) {
+                        stack.push(Value::Bool(quantifier.result(0, 0)));
+                        position = end;
+                    }
+                }
+                Instruction::IterateImplicit {
+                    quantifier,
+                    path,
+                    end,
+                } => {
+                    let (length, items) = implicit_elements(
+                        &program.paths[path],
+                        |prefix| resolve(program, prefix, document, &frames),
+                        || document.clone(),
+                    );
+                    if !iterate(&mut frames, (quantifier, position, (path, length)), items) {
+                        stack.push(Value::Bool(quantifier.result(0, 0)));
+                        position = end;
+                    }
+                }
+                Instruction::Next(start) => {
+                    let result = pop(&mut stack)?;
+                    let frame = frames
+                        .last_mut()
+                        .filter(|frame| frame.start == start)
+                        .ok_or_else(|| invalid("unmatched Next"))?;
+                    if is_truthy(&result) {
+                        frame.satisfied += 1;
+                    }
+                    frame.index += 1;
+                    if frame.index < frame.items.len() {
+                        position = start;
+                    } else {
+                        let frame = frames.pop().unwrap();
+                        let result = frame.quantifier.result(frame.satisfied, frame.items.len());
+                        stack.push(Value::Bool(result));
+                    }
+                }
+            }
+        }
+        Ok(is_truthy(&pop(&mut stack)?))
+    }
+
+    /// Keep the documents matching the program.
+    pub fn filter<'a>(
+        &self,
+        program: &Program,
+        documents: &'a [Value],
+    ) -> Result<Vec<&'a Value>, EvaluatorError> {
+        let mut matches = vec![];
+        for document in documents {
+            if self.matches(program, document)? {
+                matches.push(document);
+            }
+        }
+        Ok(matches)
+    }
+}
+
+/// String constant.
+fn text(program: &Program, index: usize) -> &str {
+    program.constants[index].as_str().unwrap()
+}
+
+/// Value of a path, from the innermost element whose prefix starts it or
+/// else from the document.
+fn resolve(program: &Program, path: &[String], document: &Value, frames: &[Frame]) -> Value {
+    for frame in frames.iter().rev() {
+        let (prefix, length) = frame.prefix;
+        let prefix = &program.paths[prefix][..length];
+        if path.starts_with(prefix) {
+            return lookup(&frame.items[frame.index], &path[length..]);
+        }
+    }
+    lookup(document, path)
+}
+
+/// Bind the first element, `false` without elements.
+fn iterate(
+    frames: &mut Vec<Frame>,
+    (quantifier, start, prefix): (Quantifier, usize, (usize, usize)),
+    items: Vec<Value>,
+) -> bool {
+    if items.is_empty() {
+        return false;
+    }
+    frames.push(Frame {
+        quantifier,
+        start,
+        prefix,
+        items,
+        index: 0,
+        satisfied: 0,
+    });
+    true
+}
+
+fn pop(stack: &mut Vec<Value>) -> Result<Value, EvaluatorError> {
+    stack.pop().ok_or_else(underflow)
+}
+
+fn pop_many(stack: &mut Vec<Value>, count: usize) -> Result<Vec<Value>, EvaluatorError> {
+    let start = stack.len().checked_sub(count).ok_or_else(underflow)?;
+    Ok(stack.split_off(start))
+}
+
+fn underflow() -> EvaluatorError {
+    invalid("stack underflow")
+}
+
+fn invalid(reason: &str) -> EvaluatorError {
+    EvaluatorError::InvalidProgram(reason.to_string())
+}
@@ -0,0 +1,19 @@
+//! Translate an [`crate::ast::AST`] to code of other languages, so a query
+//! can run where the documents are.
+//!
+//! Calls are translated with the templates registered for each target in the
+//! [`crate::functions::FunctionRegistry`].
+//!
+//! Every target sorts like the evaluator, a stable sort where `null` and
+//! missing values go last unless `NULLS FIRST`, in either direction, and
+//! other values are ordered by type, booleans, numbers and then strings, and
+//! within a type by value. Arrays and objects go after strings and are equal
+//! among them. Each target documents where it differs.
+
+mod codegen_error;
+mod codegen_options;
+mod typescript;
+
+pub use self::codegen_error::CodegenError;
+pub use self::codegen_options::CodegenOptions;
+pub use self::typescript::TypeScript;
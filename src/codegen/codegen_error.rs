@@ -0,0 +1,14 @@
+use crate::tokenizer::token::Span;
+
+#[derive(Debug)]
+pub enum CodegenError {
+    StatementNotSupported(Span),
+    FunctionNotFound(String, Span),
+    /// The function was called with a number of arguments other than its arity.
+    FunctionArity(String, Span),
+    /// The function has no template for the target.
+    TranslationNotFound(String, Span),
+    /// The regex doesn't compile, with the message and the span of the
+    /// regex.
+    InvalidRegex(String, Span),
+}
@@ -0,0 +1,29 @@
+use crate::functions::FunctionRegistry;
+
+#[derive(Debug, Clone)]
+pub struct CodegenOptions {
+    /// Fields where the free text of the query is looked up.
+    pub default_fields: Vec<String>,
+    /// Functions callable from the query.
+    pub functions: FunctionRegistry,
+}
+
+impl CodegenOptions {
+    pub fn set_default_fields<A: ToString>(&mut self, default_fields: Vec<A>) -> &mut Self {
+        self.default_fields = default_fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+    pub fn set_functions(&mut self, functions: FunctionRegistry) -> &mut Self {
+        self.functions = functions;
+        self
+    }
+}
+
+impl Default for CodegenOptions {
+    fn default() -> Self {
+        Self {
+            default_fields: vec!["title".to_string()],
+            functions: FunctionRegistry::default(),
+        }
+    }
+}
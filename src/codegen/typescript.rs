@@ -0,0 +1,490 @@
+use super::codegen_error::CodegenError;
+use super::codegen_options::CodegenOptions;
+use crate::analyzer::{Analyzer, TextAnalyzer};
+use crate::ast::statement::Statement;
+use crate::ast::value::Value;
+use crate::ast::{Stage, AST};
+use crate::evaluator::{compile_regex, strip_root};
+
+const TARGET: &str = "typescript";
+
+const COMPARE_VALUES: &str = r#"function compareValues(left, right, descending, nullsFirst) {
+    const rank = (value) => value === null || value === undefined ? 0
+        : typeof value === "boolean" ? 1
+        : typeof value === "number" ? 2
+        : typeof value === "string" ? 3
+        : 4
+    if (rank(left) === 0 || rank(right) === 0) {
+        return rank(left) === rank(right) ? 0 : (rank(left) === 0) === nullsFirst ? -1 : 1
+    }
+    const order = rank(left) - rank(right)
+        || (rank(left) === 4 ? 0 : left < right ? -1 : left > right ? 1 : 0)
+    return descending ? -order : order
+}"#;
+
+/// Generates a TypeScript `filter(item)` function.
+///
+/// Comparisons are translated as written, `tags:"red"` is
+/// `item.tags == "red"`, so arrays need a quantifier like
+/// `any(tags, t => t = "red")`. The collection of the implicit form
+/// `all(variants.stock > 0)` is the parent of the field, `variants`, as the
+/// shape of the documents isn't known when generating code.
+///
+/// The `sort` stages of a pipeline generate a `compare(left, right)` function
+/// for `Array.prototype.sort`, which is stable, with the ordering of the
+/// evaluator: `null` and `undefined` last unless `NULLS FIRST`, then
+/// booleans, numbers and strings, with arrays and objects at the end. Strings
+/// are compared by UTF-16 code units, so characters outside the Basic
+/// Multilingual Plane may sort differently than in the evaluator. Other
+/// stages aren't supported.
+///
+/// Wildcards become regexes, `lapt*` a word of the default fields ignoring
+/// case and `brand:ac*` the whole string; SQL backends would use `LIKE` with
+/// `%` for `*` and `_` for `?`.
+///
+/// Phrases like `title:"quick fox"` become regexes too, while fuzzy terms
+/// like `colour~1` and phrases with words between like `"quick fox"~3`
+/// aren't supported. Boosts like `quick^2` only weigh when scoring and are
+/// left out. Free text is compared like the standard analyzer does, other
+/// analyzers of the evaluator aren't known to the generated code.
+///
+/// Regex literals are checked to compile for the evaluator and copied as
+/// JavaScript regex literals, so syntax that only one of both engines knows,
+/// like lookarounds, may behave differently.
+pub struct TypeScript {
+    options: CodegenOptions,
+}
+
+impl TypeScript {
+    pub fn new(options: &CodegenOptions) -> Self {
+        Self {
+            options: options.clone(),
+        }
+    }
+
+    pub fn generate(&self, ast: &AST) -> Result<String, CodegenError> {
+        let (statement, stages) = match ast.statement() {
+            Statement::Pipeline { source, stages, .. } => (&**source, &stages[..]),
+            statement => (statement, &[][..]),
+        };
+        let body = match statement {
+            Statement::Expression { expresssion, .. } if !expresssion.is_empty() => {
+                let mut conditions = vec![];
+                for statement in expresssion {
+                    conditions.push(self.statement(statement, &[])?);
+                }
+                conditions.join("\n    && ")
+            }
+            Statement::Expression { .. } => "true".to_string(),
+            statement => self.statement(statement, &[])?,
+        };
+        let mut code = format!("function filter(item) {{\n    return {}\n}}", body);
+
+        // Sorting again by other keys keeps the previous order for ties, so
+        // the keys of the last `sort` go first.
+        let mut keys = vec![];
+        for stage in stages {
+            match stage {
+                Stage::Sort {
+                    keys: stage_keys, ..
+                } => {
+                    keys.splice(0..0, stage_keys.iter());
+                }
+                stage => return Err(CodegenError::StatementNotSupported(stage.clone().to_span())),
+            }
+        }
+        if !keys.is_empty() {
+            let mut comparisons = vec![];
+            for key in keys {
+                comparisons.push(format!(
+                    "compareValues({}, {}, {}, {})",
+                    self.statement(&key.expression, &[(vec![], "left".to_string())])?,
+                    self.statement(&key.expression, &[(vec![], "right".to_string())])?,
+                    key.descending,
+                    key.nulls_first
+                ));
+            }
+            code.push_str(&format!(
+                "\n\nfunction compare(left, right) {{\n    return {}\n}}\n\n{}",
+                comparisons.join("\n        || "),
+                COMPARE_VALUES
+            ));
+        }
+
+        Ok(code)
+    }
+
+    /// Regex literal of the right side of `=~`, checked to compile with the
+    /// flags of the evaluator.
+    fn regex(
+        &self,
+        statement: &Statement,
+        scope: &[(Vec<String>, String)],
+    ) -> Result<String, CodegenError> {
+        match statement {
+            Statement::Literal {
+                span,
+                value: Value::Regex(pattern, flags),
+            } => {
+                compile_regex(pattern, flags)
+                    .map_err(|message| CodegenError::InvalidRegex(message, span.clone()))?;
+                Ok(format!("/{}/{}", escape_regex(pattern), flags))
+            }
+            statement => Ok(format!("new RegExp({})", self.statement(statement, scope)?)),
+        }
+    }
+
+    /// Test of `regex` on any of the default fields.
+    fn default_fields_test(&self, regex: &str, scope: &[(Vec<String>, String)]) -> String {
+        let conditions: Vec<String> = self
+            .options
+            .default_fields
+            .iter()
+            .map(|field| {
+                let segments: Vec<String> = field.split('.').map(|s| s.to_string()).collect();
+                string_test(&path(&segments, scope), regex)
+            })
+            .collect();
+        match conditions.len() {
+            1 => conditions[0].clone(),
+            _ => format!("({})", conditions.join(" || ")),
+        }
+    }
+
+    /// `scope` has the variables of the quantifiers, each one replacing a
+    /// path prefix.
+    fn statement(
+        &self,
+        statement: &Statement,
+        scope: &[(Vec<String>, String)],
+    ) -> Result<String, CodegenError> {
+        match statement {
+            Statement::Expression { expresssion, .. } => {
+                let mut conditions = vec![];
+                for statement in expresssion {
+                    conditions.push(self.statement(statement, scope)?);
+                }
+                Ok(match conditions.len() {
+                    0 => "true".to_string(),
+                    _ => format!("({})", conditions.join(" && ")),
+                })
+            }
+            Statement::Identifier { .. } | Statement::MemberExpression { .. } => {
+                Ok(path(&statement.to_path().unwrap_or_default(), scope))
+            }
+            Statement::Literal { value, .. } => Ok(serde_json::Value::from(value).to_string()),
+            Statement::Text { value, .. } => {
+                let text = match value {
+                    Value::String(text) => text.to_lowercase(),
+                    value => serde_json::Value::from(value).to_string(),
+                };
+                let text = serde_json::Value::from(text).to_string();
+                let conditions: Vec<String> = self
+                    .options
+                    .default_fields
+                    .iter()
+                    .map(|field| {
+                        let segments: Vec<String> =
+                            field.split('.').map(|s| s.to_string()).collect();
+                        text_test(&path(&segments, scope), &text)
+                    })
+                    .collect();
+                Ok(match conditions.len() {
+                    1 => conditions[0].clone(),
+                    _ => format!("({})", conditions.join(" || ")),
+                })
+            }
+            Statement::Wildcard { pattern, .. } => {
+                let regex = word_regex(&glob_regex(pattern, "[\\p{L}\\p{N}]"));
+                Ok(self.default_fields_test(&regex, scope))
+            }
+            Statement::Phrase {
+                phrase, slop: 0, ..
+            } => Ok(self.default_fields_test(&phrase_regex(phrase), scope)),
+            Statement::BinaryExpression {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let left = self.statement(left, scope)?;
+                match (operator.as_str(), &**right) {
+                    (
+                        ":" | "=",
+                        Statement::RangeExpression {
+                            start,
+                            end,
+                            start_exclusive,
+                            end_exclusive,
+                            ..
+                        },
+                    ) => {
+                        let mut conditions = vec![];
+                        if let Some(start) = start {
+                            conditions.push(format!(
+                                "{} {} {}",
+                                left,
+                                if *start_exclusive { ">" } else { ">=" },
+                                self.statement(start, scope)?
+                            ));
+                        }
+                        if let Some(end) = end {
+                            conditions.push(format!(
+                                "{} {} {}",
+                                left,
+                                if *end_exclusive { "<" } else { "<=" },
+                                self.statement(end, scope)?
+                            ));
+                        }
+                        Ok(format!("({})", conditions.join(" && ")))
+                    }
+                    (
+                        ":" | "=",
+                        Statement::Phrase {
+                            phrase, slop: 0, ..
+                        },
+                    ) => Ok(string_test(&left, &phrase_regex(phrase))),
+                    (":" | "=", Statement::Wildcard { pattern, .. }) => Ok(string_test(
+                        &left,
+                        &format!("/^{}$/su", glob_regex(pattern, ".")),
+                    )),
+                    ("&&" | "||", right) => Ok(format!(
+                        "({} {} {})",
+                        left,
+                        operator,
+                        self.statement(right, scope)?
+                    )),
+                    ("=~" | "!~", right) => {
+                        let regex = self.regex(right, scope)?;
+                        let test = string_test(&left, &regex);
+                        Ok(match operator.as_str() {
+                            "=~" => test,
+                            _ => format!("!{}", test),
+                        })
+                    }
+                    ("in", right) => Ok(format!(
+                        "{}.includes({})",
+                        self.statement(right, scope)?,
+                        left
+                    )),
+                    (":" | "=", right) => {
+                        Ok(format!("{} == {}", left, self.statement(right, scope)?))
+                    }
+                    (operator, right) => Ok(format!(
+                        "{} {} {}",
+                        left,
+                        operator,
+                        self.statement(right, scope)?
+                    )),
+                }
+            }
+            Statement::Not { argument, .. } => {
+                Ok(format!("!({})", self.statement(argument, scope)?))
+            }
+            Statement::Boost { argument, .. } => self.statement(argument, scope),
+            Statement::ArrayExpression { elements, .. } => {
+                let mut values = vec![];
+                for element in elements {
+                    values.push(self.statement(element, scope)?);
+                }
+                Ok(format!("[{}]", values.join(", ")))
+            }
+            Statement::ObjectExpression { properties, .. } => {
+                let mut values = vec![];
+                for (key, value) in properties {
+                    values.push(format!(
+                        "{}: {}",
+                        serde_json::Value::from(key.as_str()),
+                        self.statement(value, scope)?
+                    ));
+                }
+                Ok(format!("{{ {} }}", values.join(", ")))
+            }
+            Statement::TemplateLiteral {
+                quasis,
+                expressions,
+                ..
+            } => {
+                let mut code = format!("`{}", escape_template(&quasis[0]));
+                for (expression, quasi) in expressions.iter().zip(&quasis[1..]) {
+                    code.push_str(&format!(
+                        "${{{} ?? \"\"}}{}",
+                        self.statement(expression, scope)?,
+                        escape_template(quasi)
+                    ));
+                }
+                code.push('`');
+                Ok(code)
+            }
+            Statement::Call {
+                span,
+                callee,
+                arguments,
+            } => {
+                let function =
+                    self.options.functions.get(callee).ok_or_else(|| {
+                        CodegenError::FunctionNotFound(callee.clone(), span.clone())
+                    })?;
+                if function.signature.arity() != arguments.len() {
+                    return Err(CodegenError::FunctionArity(callee.clone(), span.clone()));
+                }
+                let mut values = vec![];
+                for argument in arguments {
+                    values.push(self.statement(argument, scope)?);
+                }
+                function
+                    .translate(TARGET, &values)
+                    .ok_or_else(|| CodegenError::TranslationNotFound(callee.clone(), span.clone()))
+            }
+            Statement::Quantifier {
+                span,
+                quantifier,
+                collection,
+                binding,
+                predicate,
+            } => {
+                let (collection, prefix) = match collection {
+                    Some(collection) => {
+                        let prefix = match binding {
+                            Some(binding) => vec![binding.clone()],
+                            None => strip_root(collection.to_path().unwrap_or_default()),
+                        };
+                        (self.statement(collection, scope)?, prefix)
+                    }
+                    None => {
+                        let mut prefix = predicate
+                            .leftmost_path()
+                            .map(strip_root)
+                            .ok_or_else(|| CodegenError::StatementNotSupported(span.clone()))?;
+                        if prefix.len() > 1 {
+                            prefix.pop();
+                        }
+                        (path(&prefix, scope), prefix)
+                    }
+                };
+                let variable = match binding {
+                    Some(binding) => binding.clone(),
+                    None => prefix.last().cloned().unwrap_or("element".to_string()),
+                };
+
+                let mut scope = scope.to_vec();
+                scope.push((prefix, variable.clone()));
+                let predicate = self.statement(predicate, &scope)?;
+
+                let items = format!("[{} ?? []].flat()", collection);
+                Ok(match quantifier.as_str() {
+                    "any" => format!("{}.some(({}) => {})", items, variable, predicate),
+                    "all" => format!("{}.every(({}) => {})", items, variable, predicate),
+                    _ => format!("!{}.some(({}) => {})", items, variable, predicate),
+                })
+            }
+            Statement::RangeExpression { span, .. }
+            | Statement::Fuzzy { span, .. }
+            | Statement::Phrase { span, .. }
+            | Statement::Select { span, .. }
+            | Statement::Pipeline { span, .. }
+            | Statement::Aggregate { span, .. } => {
+                Err(CodegenError::StatementNotSupported(span.clone()))
+            }
+        }
+    }
+}
+
+/// `(typeof x === "string" && /re/.test(x))`, as `test` would otherwise
+/// convert `undefined` to a string.
+fn string_test(value: &str, regex: &str) -> String {
+    format!("(typeof {0} === \"string\" && {1}.test({0}))", value, regex)
+}
+
+/// Test of a string containing the lowercase text, ignoring case.
+fn text_test(value: &str, text: &str) -> String {
+    format!(
+        "(typeof {0} === \"string\" && {0}.toLowerCase().includes({1}))",
+        value, text
+    )
+}
+
+/// Regex matching `source` as whole words, ignoring case.
+fn word_regex(source: &str) -> String {
+    format!(
+        "/(?<![\\p{{L}}\\p{{N}}]){}(?![\\p{{L}}\\p{{N}}])/iu",
+        source
+    )
+}
+
+/// Regex of the words of a phrase with only other characters between them,
+/// as split by the standard analyzer.
+fn phrase_regex(phrase: &str) -> String {
+    word_regex(
+        &TextAnalyzer::standard()
+            .analyze(phrase)
+            .join("[^\\p{L}\\p{N}]+"),
+    )
+}
+
+/// Regex source of a wildcard, `*` being any run of `any` and `?` one `any`.
+fn glob_regex(pattern: &str, any: &str) -> String {
+    let mut regex = String::new();
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(&format!("{}*", any)),
+            '?' => regex.push_str(any),
+            // Only syntax characters may be escaped with the `u` flag.
+            c if "^$\\.+()[]{}|/".contains(c) => {
+                regex.push('\\');
+                regex.push(c);
+            }
+            c => regex.push(c),
+        }
+    }
+    regex
+}
+
+/// `/` escaped as in a JavaScript regex literal.
+fn escape_regex(pattern: &str) -> String {
+    let mut code = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                code.push(c);
+                code.extend(chars.next());
+            }
+            '/' => code.push_str("\\/"),
+            c => code.push(c),
+        }
+    }
+    code
+}
+
+fn escape_template(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('`', "\\`")
+        .replace("${", "\\${")
+}
+
+fn path(segments: &[String], scope: &[(Vec<String>, String)]) -> String {
+    let segments = strip_root(segments.to_vec());
+    let (mut code, rest) = match scope
+        .iter()
+        .rev()
+        .find(|(prefix, _)| segments.starts_with(prefix))
+    {
+        Some((prefix, variable)) => (variable.clone(), &segments[prefix.len()..]),
+        None => ("item".to_string(), &segments[..]),
+    };
+    for segment in rest {
+        let is_identifier = segment.chars().enumerate().all(|(i, c)| {
+            c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+        });
+        if is_identifier && !segment.is_empty() {
+            code.push('.');
+            code.push_str(segment);
+        } else if segment.parse::<usize>().is_ok() {
+            code.push_str(&format!("[{}]", segment));
+        } else {
+            code.push_str(&format!("[{}]", serde_json::Value::from(segment.as_str())));
+        }
+    }
+    code
+}
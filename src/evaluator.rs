@@ -0,0 +1,420 @@
+//! Evaluate an [`AST`] against JSON documents.
+//!
+//! # Arrays and scalars
+//!
+//! - A path walking through an array projects the field of each element, so
+//!   `variants.stock` over two variants is the array of both stocks.
+//! - A comparison whose field resolves to an array holds if **any** element
+//!   satisfies it. `tags:"red"` matches `{"tags": ["red", "blue"]}` and
+//!   `variants.stock > 0` matches if at least one variant has stock.
+//! - `status in ["open", "pending"]` holds if the field equals any listed
+//!   value, and `price:10..20` if it is within both bounds, inclusive. A `*`
+//!   bound, as in `price:10..*`, leaves the range open. A `<` next to the
+//!   dots excludes that bound: `price:10<..20`, `price:10..<20` and
+//!   `price:10<..<20`.
+//! - Functions of [`EvaluatorOptions::functions`] are called with the
+//!   evaluated arguments, see [`crate::functions`].
+//! - `-term`, `!field:value` and `NOT field:value` match the documents the
+//!   term doesn't, including those without the field.
+//! - Templates like `` `${brand} ${model}` `` insert strings as they are,
+//!   other values as JSON and nothing for `null` or a missing field.
+//! - Free text is compared with the analyzer of each default field, see
+//!   [`crate::analyzer`]. With the standard analyzer `Car` matches any text
+//!   containing `car` in any case.
+//! - A wildcard like `lapt*` or `col?r` matches a normalized word of the
+//!   default fields, and `brand:ac*` a whole string value as written.
+//! - A fuzzy term like `colour~1` matches a normalized word of the default
+//!   fields within that many edits, insertions, deletions or substitutions,
+//!   and `name:colour~1` a whole string value. `colour~` allows 2 edits.
+//! - A phrase like `title:"quick fox"` matches the analyzed terms of a string
+//!   value in order, and `"quick fox"~3` those of the default fields with up
+//!   to 3 other terms between them.
+//! - `name =~ /^ab.*z$/i` holds if a string, or any string of an array,
+//!   matches the regex, and `!~` if none does. Flags are `i`, `m`, `s` and
+//!   `u`, an invalid regex is an [`EvaluatorError::InvalidRegex`].
+//! - [`CompiledQuery`] evaluates a query like [`Evaluator::matches`] from
+//!   closures built once, for a query run against many documents.
+//! - [`Evaluator::explain`] evaluates every node of a query, with its span,
+//!   to tell which conditions a document fails, see [`Explanation`].
+//! - [`Evaluator::highlight`] returns the character ranges of the strings
+//!   satisfying each term, see [`Highlight`].
+//! - Documents are JSON values or any [`Queryable`], like a struct with
+//!   `#[derive(Queryable)]`, see [`crate::queryable`].
+//! - Dates like `2024-01-01` are strings compared in ISO 8601 order.
+//! - `any`, `all` and `none` quantify explicitly. A scalar is a collection of
+//!   one element and a missing field or `null` is an empty collection, so over
+//!   an empty collection `any` is `false` while `all` and `none` are `true`.
+//!
+//! ```text
+//! any(tags, t => t = "red")           every `t` is one element of `tags`
+//! any(variants, variants.stock > 0)   `variants` is one element at a time
+//! all(variants.stock > 0)             collection is the first array of the path
+//! ```
+
+mod aggregate;
+mod compiled_query;
+mod cursor;
+mod document;
+mod evaluator_error;
+mod evaluator_options;
+mod explain;
+mod facet_options;
+mod facets;
+mod fields;
+mod highlight;
+mod leaf;
+mod page;
+mod pattern;
+mod pipeline;
+mod rank;
+mod rank_options;
+mod scope;
+mod select;
+mod snippet_options;
+
+pub use self::compiled_query::CompiledQuery;
+pub use self::cursor::Cursor;
+pub(crate) use self::document::{
+    any_string, compare, edit_distance, elements, glob_matches, implicit_elements, in_range,
+    is_truthy, lookup, quantify, range_bound, texts, to_string,
+};
+pub use self::evaluator_error::EvaluatorError;
+pub use self::evaluator_options::EvaluatorOptions;
+pub use self::explain::Explanation;
+pub use self::facet_options::FacetOptions;
+pub use self::facets::{Facet, FacetValue, Facets};
+pub use self::highlight::{Highlight, TermHighlight};
+pub(crate) use self::leaf::Leaf;
+pub use self::page::Page;
+pub(crate) use self::pattern::{compile_regex, RegexCache};
+pub use self::rank_options::RankOptions;
+pub use self::snippet_options::SnippetOptions;
+
+use self::scope::Scope;
+use crate::ast::statement::Statement;
+use crate::ast::value::Value as AstValue;
+use crate::ast::AST;
+use crate::queryable::Queryable;
+use regex::Regex;
+use serde_json::Value;
+use std::sync::Arc;
+
+pub struct Evaluator {
+    options: EvaluatorOptions,
+    /// Regexes compiled by pattern and flags.
+    regexes: RegexCache,
+}
+
+impl Evaluator {
+    pub fn new(options: &EvaluatorOptions) -> Self {
+        Self {
+            options: options.clone(),
+            regexes: RegexCache::default(),
+        }
+    }
+
+    /// Regex of the right side of `=~`, a regex literal or a string with
+    /// the pattern.
+    fn regex<D: Queryable + ?Sized>(
+        &self,
+        statement: &Statement,
+        document: &D,
+        scope: &Scope,
+    ) -> Result<Arc<Regex>, EvaluatorError> {
+        let regex = match statement {
+            Statement::Literal {
+                value: AstValue::Regex(pattern, flags),
+                ..
+            } => self.regexes.get(pattern, flags),
+            statement => match self.evaluate(statement, document, scope)? {
+                Value::String(pattern) => self.regexes.get(&pattern, ""),
+                _ => Err("pattern isn't a string".to_string()),
+            },
+        };
+        regex.map_err(|message| EvaluatorError::InvalidRegex(message, statement.clone().to_span()))
+    }
+
+    /// Test if `document` matches the query, after checking its paths with
+    /// [`Evaluator::check_fields`]. The stages of a pipeline are ignored.
+    pub fn matches<D: Queryable + ?Sized>(
+        &self,
+        ast: &AST,
+        document: &D,
+    ) -> Result<bool, EvaluatorError> {
+        self.check_fields::<D>(ast)?;
+        self.test(ast, document)
+    }
+
+    /// [`Evaluator::matches`] without checking the paths, for callers
+    /// checking them once for many documents or reading JSON.
+    pub(crate) fn test<D: Queryable + ?Sized>(
+        &self,
+        ast: &AST,
+        document: &D,
+    ) -> Result<bool, EvaluatorError> {
+        let statement = match ast.statement() {
+            Statement::Pipeline { source, .. } => source,
+            statement => statement,
+        };
+        let value = self.evaluate(statement, document, &Scope::default())?;
+        Ok(is_truthy(&value))
+    }
+
+    /// Keep the documents matching the query, after checking its paths with
+    /// [`Evaluator::check_fields`].
+    pub fn filter<'a, D: Queryable>(
+        &self,
+        ast: &AST,
+        documents: &'a [D],
+    ) -> Result<Vec<&'a D>, EvaluatorError> {
+        self.check_fields::<D>(ast)?;
+        let mut matches = vec![];
+        for document in documents {
+            if self.test(ast, document)? {
+                matches.push(document);
+            }
+        }
+        Ok(matches)
+    }
+
+    fn evaluate<D: Queryable + ?Sized>(
+        &self,
+        statement: &Statement,
+        document: &D,
+        scope: &Scope,
+    ) -> Result<Value, EvaluatorError> {
+        match statement {
+            Statement::Expression { expresssion, .. } => {
+                for statement in expresssion {
+                    if !is_truthy(&self.evaluate(statement, document, scope)?) {
+                        return Ok(Value::Bool(false));
+                    }
+                }
+                Ok(Value::Bool(true))
+            }
+            Statement::Identifier { .. } | Statement::MemberExpression { .. } => {
+                let path = statement.to_path().unwrap_or_default();
+                Ok(self.resolve(&path, document, scope))
+            }
+            Statement::Literal { value, .. } => Ok(value.into()),
+            Statement::Not { argument, .. } => Ok(Value::Bool(!is_truthy(
+                &self.evaluate(argument, document, scope)?,
+            ))),
+            Statement::Boost { argument, .. } => self.evaluate(argument, document, scope),
+            Statement::ArrayExpression { elements, .. } => {
+                let mut values = vec![];
+                for element in elements {
+                    values.push(self.evaluate(element, document, scope)?);
+                }
+                Ok(Value::Array(values))
+            }
+            Statement::ObjectExpression { properties, .. } => {
+                let mut object = serde_json::Map::new();
+                for (key, value) in properties {
+                    object.insert(key.clone(), self.evaluate(value, document, scope)?);
+                }
+                Ok(Value::Object(object))
+            }
+            Statement::TemplateLiteral {
+                quasis,
+                expressions,
+                ..
+            } => {
+                let mut text = quasis[0].clone();
+                for (expression, quasi) in expressions.iter().zip(&quasis[1..]) {
+                    text.push_str(&to_string(&self.evaluate(expression, document, scope)?));
+                    text.push_str(quasi);
+                }
+                Ok(Value::String(text))
+            }
+            Statement::Call {
+                span,
+                callee,
+                arguments,
+            } => {
+                let function = self.options.functions.get(callee).ok_or_else(|| {
+                    EvaluatorError::FunctionNotFound(callee.clone(), span.clone())
+                })?;
+                if function.signature.arity() != arguments.len() {
+                    return Err(EvaluatorError::FunctionArity(callee.clone(), span.clone()));
+                }
+                let mut values = vec![];
+                for argument in arguments {
+                    values.push(self.evaluate(argument, document, scope)?);
+                }
+                Ok(function.call(&values))
+            }
+            Statement::RangeExpression { span, .. }
+            | Statement::Select { span, .. }
+            | Statement::Pipeline { span, .. }
+            | Statement::Aggregate { span, .. } => {
+                Err(EvaluatorError::StatementNotSupported(span.clone()))
+            }
+            Statement::Text { .. }
+            | Statement::Wildcard { .. }
+            | Statement::Fuzzy { .. }
+            | Statement::Phrase { .. } => {
+                Ok(Value::Bool(self.free_text(statement, document, scope)))
+            }
+            Statement::BinaryExpression {
+                span,
+                left,
+                operator,
+                right,
+            } => match operator.as_str() {
+                "&&" => Ok(Value::Bool(
+                    is_truthy(&self.evaluate(left, document, scope)?)
+                        && is_truthy(&self.evaluate(right, document, scope)?),
+                )),
+                "||" => Ok(Value::Bool(
+                    is_truthy(&self.evaluate(left, document, scope)?)
+                        || is_truthy(&self.evaluate(right, document, scope)?),
+                )),
+                "=" | ":"
+                    if matches!(
+                        **right,
+                        Statement::Wildcard { .. }
+                            | Statement::Fuzzy { .. }
+                            | Statement::Phrase { .. }
+                    ) =>
+                {
+                    let analyzer = self.options.statement_analyzer(left).as_ref();
+                    let value = self.evaluate(left, document, scope)?;
+                    Ok(Value::Bool(
+                        Leaf::field(right, analyzer)
+                            .is_some_and(|leaf| leaf.matches(&value, analyzer)),
+                    ))
+                }
+                "=" | ":" if matches!(**right, Statement::RangeExpression { .. }) => {
+                    let left = self.evaluate(left, document, scope)?;
+                    let (start, end, start_exclusive, end_exclusive) = match &**right {
+                        Statement::RangeExpression {
+                            start,
+                            end,
+                            start_exclusive,
+                            end_exclusive,
+                            ..
+                        } => (start, end, *start_exclusive, *end_exclusive),
+                        _ => unreachable!(),
+                    };
+                    let start = match start {
+                        Some(start) => Some(self.evaluate(start, document, scope)?),
+                        None => None,
+                    };
+                    let end = match end {
+                        Some(end) => Some(self.evaluate(end, document, scope)?),
+                        None => None,
+                    };
+                    Ok(Value::Bool(in_range(
+                        &left,
+                        range_bound(start.as_ref(), start_exclusive),
+                        range_bound(end.as_ref(), end_exclusive),
+                    )))
+                }
+                "=~" | "!~" => {
+                    let regex = self.regex(right, document, scope)?;
+                    let value = self.evaluate(left, document, scope)?;
+                    let matches = any_string(&value, |text| regex.is_match(text));
+                    Ok(Value::Bool(matches == (operator == "=~")))
+                }
+                "=" | ":" | ">" | "<" | ">=" | "<=" | "in" => {
+                    let left = self.evaluate(left, document, scope)?;
+                    let right = self.evaluate(right, document, scope)?;
+                    Ok(Value::Bool(compare(&left, operator, &right)))
+                }
+                _ => Err(EvaluatorError::OperatorNotSupported(
+                    operator.clone(),
+                    span.clone(),
+                )),
+            },
+            Statement::Quantifier {
+                span,
+                quantifier,
+                collection,
+                binding,
+                predicate,
+            } => {
+                let (prefix, items) = match collection {
+                    Some(collection) => {
+                        let prefix = match binding {
+                            Some(binding) => vec![binding.clone()],
+                            None => strip_root(collection.to_path().unwrap_or_default()),
+                        };
+                        (
+                            prefix,
+                            elements(self.evaluate(collection, document, scope)?),
+                        )
+                    }
+                    None => {
+                        let path = predicate.leftmost_path().map(strip_root).ok_or_else(|| {
+                            EvaluatorError::QuantifierWithoutCollection(span.clone())
+                        })?;
+                        let (length, items) = implicit_elements(
+                            &path,
+                            |prefix| self.resolve(prefix, document, scope),
+                            || self.resolve(&[], document, &Scope::default()),
+                        );
+                        (path[..length].to_vec(), items)
+                    }
+                };
+
+                let mut satisfied = 0;
+                for item in items.iter() {
+                    let scope = scope.with(prefix.clone(), item.clone());
+                    if is_truthy(&self.evaluate(predicate, document, &scope)?) {
+                        satisfied += 1;
+                    }
+                }
+
+                Ok(Value::Bool(quantify(quantifier, satisfied, items.len())))
+            }
+        }
+    }
+
+    /// Test the value of any default field with its analyzer.
+    fn free_text<D: Queryable + ?Sized>(
+        &self,
+        statement: &Statement,
+        document: &D,
+        scope: &Scope,
+    ) -> bool {
+        self.options
+            .default_field_paths()
+            .into_iter()
+            .any(|(path, analyzer)| {
+                Leaf::free_text(statement, analyzer.as_ref()).is_some_and(|leaf| {
+                    leaf.matches(&self.resolve(&path, document, scope), analyzer.as_ref())
+                })
+            })
+    }
+
+    fn resolve<D: Queryable + ?Sized>(
+        &self,
+        path: &[String],
+        document: &D,
+        scope: &Scope,
+    ) -> Value {
+        let path = strip_root(path.to_vec());
+        match scope.find(&path) {
+            Some((value, rest)) => lookup(value, rest),
+            None => document
+                .get_field(&path)
+                .map(Value::from)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new(&EvaluatorOptions::default())
+    }
+}
+
+/// `$` is the root of the document, `$.a` and `a` are the same field.
+pub(crate) fn strip_root(mut path: Vec<String>) -> Vec<String> {
+    if path.first().map(|s| s.as_str()) == Some("$") {
+        path.remove(0);
+    }
+    path
+}
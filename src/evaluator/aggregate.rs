@@ -0,0 +1,107 @@
+use super::document::{elements, number, value_key};
+use super::scope::Scope;
+use super::{Evaluator, EvaluatorError};
+use crate::ast::statement::Statement;
+use serde_json::{Map, Value};
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+
+impl Evaluator {
+    /// A row of `fields` for each group of documents with the same values of
+    /// `keys`, in order of their first document.
+    ///
+    /// Aggregates are computed over the documents of the group and other
+    /// fields are evaluated against its first document. Without keys there
+    /// is a single group, even of no documents.
+    pub(super) fn group<D: Borrow<Value>>(
+        &self,
+        keys: &[Statement],
+        fields: &[(String, Statement)],
+        documents: Vec<D>,
+    ) -> Result<Vec<Value>, EvaluatorError> {
+        let scope = Scope::default();
+        let mut groups: Vec<Vec<D>> = vec![];
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for document in documents {
+            let mut values = vec![];
+            for key in keys {
+                values.push(self.evaluate(key, document.borrow(), &scope)?);
+            }
+            let values = value_key(&Value::Array(values));
+            match positions.get(&values) {
+                Some(&position) => groups[position].push(document),
+                None => {
+                    positions.insert(values, groups.len());
+                    groups.push(vec![document]);
+                }
+            }
+        }
+        if keys.is_empty() && groups.is_empty() {
+            groups.push(vec![]);
+        }
+
+        let mut rows = vec![];
+        for group in groups {
+            let mut row = Map::new();
+            for (name, field) in fields {
+                let value = match (field, group.first()) {
+                    (
+                        Statement::Aggregate {
+                            function, argument, ..
+                        },
+                        _,
+                    ) => self.aggregate(function, argument.as_deref(), &group)?,
+                    (field, Some(document)) => self.evaluate(field, document.borrow(), &scope)?,
+                    (_, None) => Value::Null,
+                };
+                row.insert(name.clone(), value);
+            }
+            rows.push(Value::Object(row));
+        }
+        Ok(rows)
+    }
+
+    /// `count()` is the number of documents. With an argument, the elements
+    /// of arrays are aggregated one by one and `null` or missing values are
+    /// skipped. `sum`, `avg`, `min` and `max` only take numbers, and
+    /// `distinct_count` counts `1` and `1.0` once.
+    fn aggregate<D: Borrow<Value>>(
+        &self,
+        function: &str,
+        argument: Option<&Statement>,
+        documents: &[D],
+    ) -> Result<Value, EvaluatorError> {
+        let argument = match argument {
+            Some(argument) => argument,
+            None => return Ok(Value::from(documents.len())),
+        };
+        let scope = Scope::default();
+        let mut values = vec![];
+        for document in documents {
+            values.extend(elements(self.evaluate(
+                argument,
+                document.borrow(),
+                &scope,
+            )?));
+        }
+        let numbers: Vec<f64> = values.iter().filter_map(Value::as_f64).collect();
+
+        Ok(match function {
+            "count" => Value::from(values.len()),
+            "sum" => number(numbers.iter().sum()),
+            "avg" if numbers.is_empty() => Value::Null,
+            "avg" => number(numbers.iter().sum::<f64>() / numbers.len() as f64),
+            "min" => numbers
+                .iter()
+                .copied()
+                .min_by(f64::total_cmp)
+                .map_or(Value::Null, number),
+            "max" => numbers
+                .iter()
+                .copied()
+                .max_by(f64::total_cmp)
+                .map_or(Value::Null, number),
+            _ => Value::from(values.iter().map(value_key).collect::<HashSet<_>>().len()),
+        })
+    }
+}
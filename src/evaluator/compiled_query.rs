@@ -0,0 +1,492 @@
+use super::document::{
+    any_string, compare, elements, implicit_elements, in_range, is_truthy, lookup, quantify,
+    range_bound, to_string,
+};
+use super::{compile_regex, strip_root, EvaluatorError, EvaluatorOptions, Leaf, RegexCache};
+use crate::analyzer::Analyzer;
+use crate::ast::statement::Statement;
+use crate::ast::value::Value as AstValue;
+use crate::ast::AST;
+use crate::tokenizer::token::Span;
+use regex::Regex;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Elements bound by the enclosing quantifiers, innermost last, with the
+/// length of the path prefix each one replaces.
+type Frames = Vec<(usize, Value)>;
+
+type Node = Box<dyn Fn(&Value, &mut Frames) -> Result<Value, EvaluatorError> + Send + Sync>;
+
+/// Elements a quantifier walks and the length of the prefix they rebind.
+type Collection =
+    Box<dyn Fn(&Value, &mut Frames) -> Result<(usize, Vec<Value>), EvaluatorError> + Send + Sync>;
+
+/// A query compiled into a tree of closures, evaluated like
+/// [`super::Evaluator::matches`] without walking the [`AST`] again.
+///
+/// Paths are split and bound to the quantifier that rebinds them, literals
+/// converted, regexes of literals compiled, terms analyzed and functions
+/// looked up once. Errors
+/// are returned when the node is evaluated, as the evaluator does, so a
+/// failing branch that is never reached doesn't fail the query.
+pub struct CompiledQuery {
+    root: Node,
+}
+
+impl CompiledQuery {
+    /// Compile with the default [`EvaluatorOptions`].
+    pub fn compile(ast: &AST) -> Self {
+        Self::compile_with_options(ast, &EvaluatorOptions::default())
+    }
+
+    pub fn compile_with_options(ast: &AST, options: &EvaluatorOptions) -> Self {
+        let statement = match ast.statement() {
+            Statement::Pipeline { source, .. } => source,
+            statement => statement,
+        };
+        let compiler = Compiler { options };
+        Self {
+            root: compiler.compile(statement, &mut vec![]),
+        }
+    }
+
+    /// Test if `document` matches the query, the stages of a pipeline are
+    /// ignored.
+    pub fn matches(&self, document: &Value) -> Result<bool, EvaluatorError> {
+        Ok(is_truthy(&(self.root)(document, &mut vec![])?))
+    }
+
+    /// Keep the documents matching the query.
+    pub fn filter<'a>(&self, documents: &'a [Value]) -> Result<Vec<&'a Value>, EvaluatorError> {
+        let mut matches = vec![];
+        for document in documents {
+            if self.matches(document)? {
+                matches.push(document);
+            }
+        }
+        Ok(matches)
+    }
+}
+
+/// Quantifier enclosing a statement while it is compiled.
+enum Frame {
+    /// Rebinds the prefix, the name of the binding or the path of the
+    /// collection.
+    Bound(Vec<String>),
+    /// Rebinds the part of the path up to its first array, only known for
+    /// each document.
+    Implicit(Vec<String>),
+}
+
+/// Path resolved against the quantifiers that may rebind it.
+struct Resolver {
+    path: Vec<String>,
+    /// Depth of the frame and the longest prefix of the path it may rebind,
+    /// innermost first, the last one always rebinding it when it's bound.
+    frames: Vec<(usize, usize)>,
+}
+
+impl Resolver {
+    fn new(path: Vec<String>, frames: &[Frame]) -> Self {
+        let path = strip_root(path);
+        let mut resolved = vec![];
+        for (depth, frame) in frames.iter().enumerate().rev() {
+            match frame {
+                Frame::Bound(prefix) => {
+                    if path.starts_with(prefix) {
+                        resolved.push((depth, prefix.len()));
+                        break;
+                    }
+                }
+                Frame::Implicit(prefix) => {
+                    let common = path
+                        .iter()
+                        .zip(prefix)
+                        .take_while(|(left, right)| left == right)
+                        .count();
+                    resolved.push((depth, common));
+                }
+            }
+        }
+        Self {
+            path,
+            frames: resolved,
+        }
+    }
+
+    fn resolve(&self, document: &Value, frames: &Frames) -> Value {
+        for (depth, longest) in &self.frames {
+            let (prefix, value) = &frames[*depth];
+            if prefix <= longest {
+                return lookup(value, &self.path[*prefix..]);
+            }
+        }
+        lookup(document, &self.path)
+    }
+}
+
+struct Compiler<'a> {
+    options: &'a EvaluatorOptions,
+}
+
+impl Compiler<'_> {
+    fn compile(&self, statement: &Statement, frames: &mut Vec<Frame>) -> Node {
+        match statement {
+            Statement::Expression { expresssion, .. } => {
+                let conditions = self.compile_all(expresssion.iter().map(|s| &**s), frames);
+                Box::new(move |document, scope| {
+                    for condition in &conditions {
+                        if !is_truthy(&condition(document, scope)?) {
+                            return Ok(Value::Bool(false));
+                        }
+                    }
+                    Ok(Value::Bool(true))
+                })
+            }
+            Statement::Identifier { .. } | Statement::MemberExpression { .. } => {
+                let resolver = Resolver::new(statement.to_path().unwrap_or_default(), frames);
+                Box::new(move |document, scope| Ok(resolver.resolve(document, scope)))
+            }
+            Statement::Literal { value, .. } => {
+                let value = Value::from(value);
+                Box::new(move |_, _| Ok(value.clone()))
+            }
+            Statement::Not { argument, .. } => {
+                let argument = self.compile(argument, frames);
+                Box::new(move |document, scope| {
+                    Ok(Value::Bool(!is_truthy(&argument(document, scope)?)))
+                })
+            }
+            Statement::Boost { argument, .. } => self.compile(argument, frames),
+            Statement::ArrayExpression { elements, .. } => {
+                let elements = self.compile_all(elements.iter(), frames);
+                Box::new(move |document, scope| {
+                    let mut values = vec![];
+                    for element in &elements {
+                        values.push(element(document, scope)?);
+                    }
+                    Ok(Value::Array(values))
+                })
+            }
+            Statement::ObjectExpression { properties, .. } => {
+                let properties: Vec<(String, Node)> = properties
+                    .iter()
+                    .map(|(key, value)| (key.clone(), self.compile(value, frames)))
+                    .collect();
+                Box::new(move |document, scope| {
+                    let mut object = serde_json::Map::new();
+                    for (key, value) in &properties {
+                        object.insert(key.clone(), value(document, scope)?);
+                    }
+                    Ok(Value::Object(object))
+                })
+            }
+            Statement::TemplateLiteral {
+                quasis,
+                expressions,
+                ..
+            } => {
+                let quasis = quasis.clone();
+                let expressions = self.compile_all(expressions.iter(), frames);
+                Box::new(move |document, scope| {
+                    let mut text = quasis[0].clone();
+                    for (expression, quasi) in expressions.iter().zip(&quasis[1..]) {
+                        text.push_str(&to_string(&expression(document, scope)?));
+                        text.push_str(quasi);
+                    }
+                    Ok(Value::String(text))
+                })
+            }
+            Statement::Call {
+                span,
+                callee,
+                arguments,
+            } => {
+                let Some(function) = self.options.functions.get(callee).cloned() else {
+                    return fail(EvaluatorError::FunctionNotFound(
+                        callee.clone(),
+                        span.clone(),
+                    ));
+                };
+                if function.signature.arity() != arguments.len() {
+                    return fail(EvaluatorError::FunctionArity(callee.clone(), span.clone()));
+                }
+                let arguments = self.compile_all(arguments.iter(), frames);
+                Box::new(move |document, scope| {
+                    let mut values = vec![];
+                    for argument in &arguments {
+                        values.push(argument(document, scope)?);
+                    }
+                    Ok(function.call(&values))
+                })
+            }
+            Statement::RangeExpression { span, .. }
+            | Statement::Select { span, .. }
+            | Statement::Pipeline { span, .. }
+            | Statement::Aggregate { span, .. } => {
+                fail(EvaluatorError::StatementNotSupported(span.clone()))
+            }
+            Statement::Text { .. }
+            | Statement::Wildcard { .. }
+            | Statement::Fuzzy { .. }
+            | Statement::Phrase { .. } => self.free_text(statement, frames),
+            Statement::BinaryExpression {
+                span,
+                left,
+                operator,
+                right,
+            } => self.compile_binary(span, left, operator, right, frames),
+            Statement::Quantifier {
+                span,
+                quantifier,
+                collection,
+                binding,
+                predicate,
+            } => {
+                let collection: Collection = match collection {
+                    Some(collection) => {
+                        let prefix = match binding {
+                            Some(binding) => vec![binding.clone()],
+                            None => strip_root(collection.to_path().unwrap_or_default()),
+                        };
+                        let length = prefix.len();
+                        let collection = self.compile(collection, frames);
+                        frames.push(Frame::Bound(prefix));
+                        Box::new(move |document, scope| {
+                            Ok((length, elements(collection(document, scope)?)))
+                        })
+                    }
+                    None => {
+                        let Some(path) = predicate.leftmost_path().map(strip_root) else {
+                            return fail(EvaluatorError::QuantifierWithoutCollection(span.clone()));
+                        };
+                        let prefixes: Vec<Resolver> = (1..=path.len())
+                            .map(|length| Resolver::new(path[..length].to_vec(), frames))
+                            .collect();
+                        frames.push(Frame::Implicit(path.clone()));
+                        Box::new(move |document, scope| {
+                            Ok(implicit_elements(
+                                &path,
+                                |prefix| prefixes[prefix.len() - 1].resolve(document, scope),
+                                || document.clone(),
+                            ))
+                        })
+                    }
+                };
+                let predicate = self.compile(predicate, frames);
+                frames.pop();
+
+                let quantifier = quantifier.clone();
+                Box::new(move |document, scope| {
+                    let (prefix, items) = collection(document, scope)?;
+                    let count = items.len();
+                    let mut satisfied = 0;
+                    for item in items {
+                        scope.push((prefix, item));
+                        let result = predicate(document, scope);
+                        scope.pop();
+                        if is_truthy(&result?) {
+                            satisfied += 1;
+                        }
+                    }
+                    Ok(Value::Bool(quantify(&quantifier, satisfied, count)))
+                })
+            }
+        }
+    }
+
+    fn compile_binary(
+        &self,
+        span: &Span,
+        left: &Statement,
+        operator: &str,
+        right: &Statement,
+        frames: &mut Vec<Frame>,
+    ) -> Node {
+        let operator = operator.to_string();
+        match (operator.as_str(), right) {
+            ("&&" | "||", _) => {
+                let left = self.compile(left, frames);
+                let right = self.compile(right, frames);
+                let and = operator == "&&";
+                Box::new(move |document, scope| {
+                    let left = is_truthy(&left(document, scope)?);
+                    let result = match and {
+                        true => left && is_truthy(&right(document, scope)?),
+                        false => left || is_truthy(&right(document, scope)?),
+                    };
+                    Ok(Value::Bool(result))
+                })
+            }
+            (
+                "=" | ":",
+                Statement::Wildcard { .. } | Statement::Fuzzy { .. } | Statement::Phrase { .. },
+            ) => {
+                let analyzer = self.options.statement_analyzer(left).clone();
+                let leaf = Leaf::field(right, analyzer.as_ref());
+                let left = self.compile(left, frames);
+                Box::new(move |document, scope| {
+                    let value = left(document, scope)?;
+                    Ok(Value::Bool(leaf.as_ref().is_some_and(|leaf| {
+                        leaf.matches(&value, analyzer.as_ref())
+                    })))
+                })
+            }
+            (
+                "=" | ":",
+                Statement::RangeExpression {
+                    start,
+                    end,
+                    start_exclusive,
+                    end_exclusive,
+                    ..
+                },
+            ) => {
+                let (start_exclusive, end_exclusive) = (*start_exclusive, *end_exclusive);
+                let left = self.compile(left, frames);
+                let start = start.as_ref().map(|start| self.compile(start, frames));
+                let end = end.as_ref().map(|end| self.compile(end, frames));
+                Box::new(move |document, scope| {
+                    let left = left(document, scope)?;
+                    let start = match &start {
+                        Some(start) => Some(start(document, scope)?),
+                        None => None,
+                    };
+                    let end = match &end {
+                        Some(end) => Some(end(document, scope)?),
+                        None => None,
+                    };
+                    Ok(Value::Bool(in_range(
+                        &left,
+                        range_bound(start.as_ref(), start_exclusive),
+                        range_bound(end.as_ref(), end_exclusive),
+                    )))
+                })
+            }
+            ("=~" | "!~", right) => {
+                let expected = operator == "=~";
+                // A literal pattern, a regex or a string, is compiled once.
+                let literal = match right {
+                    Statement::Literal {
+                        value: AstValue::Regex(pattern, flags),
+                        ..
+                    } => Some(compile_regex(pattern, flags)),
+                    Statement::Literal {
+                        value: AstValue::String(pattern),
+                        ..
+                    } => Some(compile_regex(pattern, "")),
+                    _ => None,
+                };
+                let regex = match literal {
+                    Some(Ok(regex)) => Pattern::Literal(regex),
+                    Some(Err(message)) => {
+                        return fail(EvaluatorError::InvalidRegex(message, span_of(right)))
+                    }
+                    None => Pattern::Dynamic(
+                        self.compile(right, frames),
+                        span_of(right),
+                        RegexCache::default(),
+                    ),
+                };
+                let left = self.compile(left, frames);
+                Box::new(move |document, scope| {
+                    let value = left(document, scope)?;
+                    let matches = regex.with(document, scope, |regex| {
+                        any_string(&value, |text| regex.is_match(text))
+                    })?;
+                    Ok(Value::Bool(matches == expected))
+                })
+            }
+            ("=" | ":" | ">" | "<" | ">=" | "<=" | "in", right) => {
+                let left = self.compile(left, frames);
+                match right {
+                    // Compared as is, without a copy for each document.
+                    Statement::Literal { value, .. } => {
+                        let right = Value::from(value);
+                        Box::new(move |document, scope| {
+                            let left = left(document, scope)?;
+                            Ok(Value::Bool(compare(&left, &operator, &right)))
+                        })
+                    }
+                    right => {
+                        let right = self.compile(right, frames);
+                        Box::new(move |document, scope| {
+                            let left = left(document, scope)?;
+                            let right = right(document, scope)?;
+                            Ok(Value::Bool(compare(&left, &operator, &right)))
+                        })
+                    }
+                }
+            }
+            _ => fail(EvaluatorError::OperatorNotSupported(operator, span.clone())),
+        }
+    }
+
+    fn compile_all<'s>(
+        &self,
+        statements: impl Iterator<Item = &'s Statement>,
+        frames: &mut Vec<Frame>,
+    ) -> Vec<Node> {
+        statements
+            .map(|statement| self.compile(statement, frames))
+            .collect()
+    }
+
+    /// Free text over the default fields, its leaf prepared for the
+    /// analyzer of each field.
+    fn free_text(&self, statement: &Statement, frames: &[Frame]) -> Node {
+        let fields: Vec<(Resolver, Arc<dyn Analyzer>, Leaf)> = self
+            .options
+            .default_field_paths()
+            .into_iter()
+            .filter_map(|(path, analyzer)| {
+                let leaf = Leaf::free_text(statement, analyzer.as_ref())?;
+                Some((Resolver::new(path, frames), analyzer.clone(), leaf))
+            })
+            .collect();
+        Box::new(move |document, scope| {
+            Ok(Value::Bool(fields.iter().any(
+                |(resolver, analyzer, leaf)| {
+                    leaf.matches(&resolver.resolve(document, scope), analyzer.as_ref())
+                },
+            )))
+        })
+    }
+}
+
+/// Right side of `=~`, a literal or a string with the pattern only known
+/// for each document.
+enum Pattern {
+    Literal(Regex),
+    Dynamic(Node, Span, RegexCache),
+}
+
+impl Pattern {
+    fn with<T, F>(&self, document: &Value, scope: &mut Frames, f: F) -> Result<T, EvaluatorError>
+    where
+        F: FnOnce(&Regex) -> T,
+    {
+        match self {
+            Pattern::Literal(regex) => Ok(f(regex)),
+            Pattern::Dynamic(pattern, span, regexes) => {
+                let regex = match pattern(document, scope)? {
+                    Value::String(pattern) => regexes.get(&pattern, ""),
+                    _ => Err("pattern isn't a string".to_string()),
+                };
+                let regex =
+                    regex.map_err(|message| EvaluatorError::InvalidRegex(message, span.clone()))?;
+                Ok(f(&regex))
+            }
+        }
+    }
+}
+
+/// Node failing with `error` when it is evaluated.
+fn fail(error: EvaluatorError) -> Node {
+    Box::new(move |_, _| Err(error.clone()))
+}
+
+fn span_of(statement: &Statement) -> Span {
+    statement.clone().to_span()
+}
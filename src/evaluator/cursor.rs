@@ -0,0 +1,31 @@
+use super::EvaluatorError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::Value;
+
+/// Position after the last document of a page, the values of its sort keys.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    pub values: Vec<Value>,
+}
+
+impl Cursor {
+    pub fn new(values: Vec<Value>) -> Self {
+        Self { values }
+    }
+
+    /// URL-safe base64 of the values as a JSON array, without padding.
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Value::Array(self.values.clone()).to_string())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, EvaluatorError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| EvaluatorError::InvalidCursor(cursor.to_string()))?;
+        match serde_json::from_slice(&bytes) {
+            Ok(Value::Array(values)) => Ok(Self { values }),
+            _ => Err(EvaluatorError::InvalidCursor(cursor.to_string())),
+        }
+    }
+}
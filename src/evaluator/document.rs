@@ -0,0 +1,382 @@
+use crate::analyzer::Analyzer;
+use crate::ast::SortKey;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::ops::{Bound, Range};
+
+/// Resolve `path` inside `value`.
+///
+/// Walking through an array with a field name projects the field of every
+/// element, so `variants.stock` on `{"variants": [{"stock": 1}, {"stock": 0}]}`
+/// is `[1, 0]`. A numeric segment on an array selects the element at that
+/// index. Missing fields resolve to `null`.
+pub(crate) fn lookup(value: &Value, path: &[String]) -> Value {
+    match path.split_first() {
+        None => value.clone(),
+        Some((segment, rest)) => match value {
+            Value::Object(map) => match map.get(segment) {
+                Some(field) => lookup(field, rest),
+                None => Value::Null,
+            },
+            Value::Array(items) => {
+                if let Ok(index) = segment.parse::<usize>() {
+                    return match items.get(index) {
+                        Some(item) => lookup(item, rest),
+                        None => Value::Null,
+                    };
+                }
+                let mut projection = vec![];
+                for item in items {
+                    match lookup(item, path) {
+                        Value::Null => {}
+                        Value::Array(values) => projection.extend(values),
+                        value => projection.push(value),
+                    }
+                }
+                Value::Array(projection)
+            }
+            _ => Value::Null,
+        },
+    }
+}
+
+/// Elements a quantifier walks: arrays yield their items, `null` yields
+/// nothing and any other value is a collection of one element.
+pub(crate) fn elements(value: Value) -> Vec<Value> {
+    match value {
+        Value::Array(items) => items,
+        Value::Null => vec![],
+        value => vec![value],
+    }
+}
+
+/// Elements of a quantifier without collection, like `all(variants.stock >
+/// 0)`: those of the first array on the path of its predicate, with the
+/// length of the prefix of the path they rebind. Without arrays on the path
+/// the document itself is the only element.
+pub(crate) fn implicit_elements(
+    path: &[String],
+    resolve: impl Fn(&[String]) -> Value,
+    document: impl FnOnce() -> Value,
+) -> (usize, Vec<Value>) {
+    (1..=path.len())
+        .map(|length| (length, resolve(&path[..length])))
+        .find(|(_, value)| value.is_array())
+        .map(|(length, value)| (length, elements(value)))
+        .unwrap_or_else(|| (0, vec![document()]))
+}
+
+/// Result of `any`, `all` or `none` for the number of elements satisfying
+/// the predicate.
+pub(crate) fn quantify(quantifier: &str, satisfied: usize, count: usize) -> bool {
+    match quantifier {
+        "any" => satisfied > 0,
+        "all" => satisfied == count,
+        _ => satisfied == 0,
+    }
+}
+
+/// Test if a string, or any string of an array, passes.
+pub(crate) fn any_string(value: &Value, test: impl Fn(&str) -> bool) -> bool {
+    match value {
+        Value::Array(items) => items.iter().any(|item| item.as_str().is_some_and(&test)),
+        value => value.as_str().is_some_and(test),
+    }
+}
+
+pub(crate) fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+/// Compare a document value with a query value.
+///
+/// When the document value is an array and the query value is not, the
+/// comparison holds if any element satisfies it: `tags:"red"` matches
+/// `{"tags": ["red", "blue"]}`. Values of different types are never equal
+/// nor ordered.
+pub(crate) fn compare(left: &Value, operator: &str, right: &Value) -> bool {
+    if let (Value::Array(items), false) = (left, right.is_array() && operator != "in") {
+        return items.iter().any(|item| compare(item, operator, right));
+    }
+
+    match operator {
+        "=" | ":" => partial_cmp(left, right) == Some(Ordering::Equal) || left == right,
+        ">" => partial_cmp(left, right) == Some(Ordering::Greater),
+        "<" => partial_cmp(left, right) == Some(Ordering::Less),
+        ">=" => matches!(
+            partial_cmp(left, right),
+            Some(Ordering::Greater | Ordering::Equal)
+        ),
+        "<=" => matches!(
+            partial_cmp(left, right),
+            Some(Ordering::Less | Ordering::Equal)
+        ),
+        "in" => match right {
+            Value::Array(values) => values.iter().any(|value| compare(left, "=", value)),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Test if `value` is within the bounds. Like [`compare`], an array is
+/// within the range if any element is.
+pub(crate) fn in_range(value: &Value, start: Bound<&Value>, end: Bound<&Value>) -> bool {
+    if let Value::Array(items) = value {
+        return items.iter().any(|item| in_range(item, start, end));
+    }
+
+    let after_start = match start {
+        Bound::Included(start) => compare(value, ">=", start),
+        Bound::Excluded(start) => compare(value, ">", start),
+        Bound::Unbounded => true,
+    };
+    let before_end = match end {
+        Bound::Included(end) => compare(value, "<=", end),
+        Bound::Excluded(end) => compare(value, "<", end),
+        Bound::Unbounded => true,
+    };
+    after_start && before_end
+}
+
+/// Bound of a range, `None` being unbounded.
+pub(crate) fn range_bound(value: Option<&Value>, exclusive: bool) -> Bound<&Value> {
+    match (value, exclusive) {
+        (Some(value), false) => Bound::Included(value),
+        (Some(value), true) => Bound::Excluded(value),
+        (None, _) => Bound::Unbounded,
+    }
+}
+
+/// Order of two values of a sort key.
+///
+/// `null` goes first or last whatever the direction. Other values are
+/// ordered by type, booleans, numbers and then strings, and within a type by
+/// value. Arrays and objects go after strings and are equal among them.
+pub(crate) fn sort_cmp(left: &Value, right: &Value, key: &SortKey) -> Ordering {
+    match (left.is_null(), right.is_null()) {
+        (true, true) => Ordering::Equal,
+        (true, false) if key.nulls_first => Ordering::Less,
+        (true, false) => Ordering::Greater,
+        (false, true) if key.nulls_first => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) if key.descending => value_cmp(left, right).reverse(),
+        (false, false) => value_cmp(left, right),
+    }
+}
+
+/// Order by type and then by value, used by [`sort_cmp`] and `min`/`max`.
+pub(crate) fn value_cmp(left: &Value, right: &Value) -> Ordering {
+    type_rank(left)
+        .cmp(&type_rank(right))
+        .then_with(|| partial_cmp(left, right).unwrap_or(Ordering::Equal))
+}
+
+/// A JSON number, integral when it has no fraction.
+pub(crate) fn number(number: f64) -> Value {
+    match number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+        true => Value::from(number as i64),
+        false => Value::from(number),
+    }
+}
+
+/// Key of a value where numbers are written the same whether integral floats
+/// or integers, so `1` and `1.0` are the same key.
+pub(crate) fn value_key(value: &Value) -> String {
+    fn normalize(value: &Value) -> Value {
+        match value {
+            Value::Number(value) => value.as_f64().map_or(Value::Null, number),
+            Value::Array(values) => Value::Array(values.iter().map(normalize).collect()),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), normalize(value)))
+                    .collect(),
+            ),
+            value => value.clone(),
+        }
+    }
+    normalize(value).to_string()
+}
+
+fn type_rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) | Value::Object(_) => 4,
+    }
+}
+
+fn partial_cmp(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => l.as_f64()?.partial_cmp(&r.as_f64()?),
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        (Value::Bool(l), Value::Bool(r)) => Some(l.cmp(r)),
+        _ => None,
+    }
+}
+
+/// Text of a value inserted in a template.
+pub(crate) fn to_string(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// Glob match of the whole text, `*` is any run of characters and `?` is
+/// one character.
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and of the text it stands for, to retry with
+    // one more character when the rest fails.
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Test the normalized words of free text, see [`crate::analyzer`].
+pub(crate) fn any_word(
+    value: &Value,
+    analyzer: &dyn Analyzer,
+    test: &dyn Fn(&str) -> bool,
+) -> bool {
+    texts(value)
+        .iter()
+        .any(|text| analyzer.words(text).iter().any(|word| test(word)))
+}
+
+/// Strings of a value for free text, numbers as written.
+pub(crate) fn texts(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(text) => vec![text.clone()],
+        Value::Number(number) => vec![number.to_string()],
+        Value::Array(items) => items.iter().flat_map(texts).collect(),
+        _ => vec![],
+    }
+}
+
+/// Test if the terms of a phrase are among the analyzed terms of a text in
+/// order, with at most `slop` other terms between them in total. A phrase
+/// without terms, like `"- -"`, matches nothing.
+pub(crate) fn phrase_matches(
+    value: &Value,
+    analyzer: &dyn Analyzer,
+    phrase: &[String],
+    slop: usize,
+) -> bool {
+    texts(value)
+        .iter()
+        .any(|text| !phrase_spans(text, analyzer, phrase, slop).is_empty())
+}
+
+/// Byte ranges of a text from the first to the last term of each match of
+/// a phrase, see [`phrase_matches`].
+pub(crate) fn phrase_spans(
+    text: &str,
+    analyzer: &dyn Analyzer,
+    phrase: &[String],
+    slop: usize,
+) -> Vec<Range<usize>> {
+    let Some((first, rest)) = phrase.split_first() else {
+        return vec![];
+    };
+    let terms: Vec<(Range<usize>, String)> = analyzer
+        .token_ranges(text)
+        .into_iter()
+        .filter_map(|range| Some((range.clone(), analyzer.filter(&text[range])?)))
+        .collect();
+    let mut spans = vec![];
+    for (start, (range, term)) in terms.iter().enumerate() {
+        if term != first {
+            continue;
+        }
+        // The nearest occurrence of each next term leaves the most slop for
+        // the terms after it.
+        let (mut position, mut slop) = (start, slop);
+        let found = rest.iter().all(|next| {
+            let found = terms[position + 1..]
+                .iter()
+                .take(slop.saturating_add(1))
+                .position(|(_, term)| term == next);
+            match found {
+                Some(skipped) => {
+                    position += skipped + 1;
+                    slop -= skipped;
+                    true
+                }
+                None => false,
+            }
+        });
+        if found {
+            spans.push(range.start..terms[position].0.end);
+        }
+    }
+    spans
+}
+
+/// Levenshtein distance between `left` and `right` if it is at most `max`.
+///
+/// Only the cells within `max` of the diagonal are filled, and the search
+/// stops as soon as a row has no cell within `max`.
+pub(crate) fn edit_distance(left: &str, right: &str, max: usize) -> Option<usize> {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+    if left.len().abs_diff(right.len()) > max {
+        return None;
+    }
+    let unreachable = max.saturating_add(1);
+    let mut previous: Vec<usize> = (0..=right.len())
+        .map(|j| if j <= max { j } else { unreachable })
+        .collect();
+    for (i, l) in left.iter().enumerate() {
+        let mut row = vec![unreachable; right.len() + 1];
+        if i < max {
+            row[0] = i + 1;
+        }
+        let from = (i + 1).saturating_sub(max).max(1);
+        let to = (i + 1).saturating_add(max).min(right.len());
+        for j in from..=to {
+            let cost = usize::from(*l != right[j - 1]);
+            row[j] = (previous[j - 1] + cost)
+                .min(previous[j] + 1)
+                .min(row[j - 1] + 1)
+                .min(unreachable);
+        }
+        if row.iter().all(|distance| *distance > max) {
+            return None;
+        }
+        previous = row;
+    }
+    Some(previous[right.len()]).filter(|distance| *distance <= max)
+}
@@ -0,0 +1,24 @@
+use crate::tokenizer::token::Span;
+
+#[derive(Debug, Clone)]
+pub enum EvaluatorError {
+    OperatorNotSupported(String, Span),
+    StatementNotSupported(Span),
+    CollectionNotFound(String, Span),
+    QuantifierWithoutCollection(Span),
+    FunctionNotFound(String, Span),
+    /// The cursor isn't one encoded by [`super::Cursor::encode`] for the sort
+    /// keys of the query.
+    InvalidCursor(String),
+    /// The regex doesn't compile, with the message and the span of the
+    /// regex.
+    InvalidRegex(String, Span),
+    /// The bytes aren't a program encoded by
+    /// [`crate::bytecode::Program::encode`], with the reason.
+    InvalidProgram(String),
+    /// The path isn't a field of the documents, see
+    /// [`crate::queryable::Queryable::has_field`].
+    UnknownField(String, Span),
+    /// The function was called with a number of arguments other than its arity.
+    FunctionArity(String, Span),
+}
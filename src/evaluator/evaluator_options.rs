@@ -0,0 +1,82 @@
+use super::strip_root;
+use crate::analyzer::{Analyzer, TextAnalyzer};
+use crate::ast::statement::Statement;
+use crate::functions::FunctionRegistry;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct EvaluatorOptions {
+    /// Fields where the free text of the query is looked up.
+    pub default_fields: Vec<String>,
+    /// Functions callable from the query.
+    pub functions: FunctionRegistry,
+    /// Analyzer of each text field by path, see [`crate::analyzer`].
+    pub analyzers: HashMap<String, Arc<dyn Analyzer>>,
+    /// Analyzer of the fields without one, [`TextAnalyzer::standard`] by
+    /// default.
+    pub default_analyzer: Arc<dyn Analyzer>,
+}
+
+impl EvaluatorOptions {
+    pub fn set_default_fields<A: ToString>(&mut self, default_fields: Vec<A>) -> &mut Self {
+        self.default_fields = default_fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+    pub fn set_functions(&mut self, functions: FunctionRegistry) -> &mut Self {
+        self.functions = functions;
+        self
+    }
+    pub fn set_analyzer<A: ToString>(
+        &mut self,
+        field: A,
+        analyzer: Arc<dyn Analyzer>,
+    ) -> &mut Self {
+        self.analyzers.insert(field.to_string(), analyzer);
+        self
+    }
+    pub fn set_default_analyzer(&mut self, default_analyzer: Arc<dyn Analyzer>) -> &mut Self {
+        self.default_analyzer = default_analyzer;
+        self
+    }
+
+    /// Analyzer of a field, a path like `title` or `specs.color`.
+    pub fn analyzer(&self, field: &str) -> &dyn Analyzer {
+        self.shared_analyzer(field).as_ref()
+    }
+
+    pub(crate) fn shared_analyzer(&self, field: &str) -> &Arc<dyn Analyzer> {
+        self.analyzers.get(field).unwrap_or(&self.default_analyzer)
+    }
+
+    /// Analyzer of the field a statement names, like the left side of
+    /// `title:"quick fox"`, the default analyzer for other statements.
+    pub(crate) fn statement_analyzer(&self, statement: &Statement) -> &Arc<dyn Analyzer> {
+        match statement.to_path() {
+            Some(path) => self.shared_analyzer(&strip_root(path).join(".")),
+            None => &self.default_analyzer,
+        }
+    }
+
+    /// Path of each default field with its analyzer.
+    pub(crate) fn default_field_paths(&self) -> Vec<(Vec<String>, &Arc<dyn Analyzer>)> {
+        self.default_fields
+            .iter()
+            .map(|field| {
+                let path = field.split('.').map(|s| s.to_string()).collect();
+                (path, self.shared_analyzer(field))
+            })
+            .collect()
+    }
+}
+
+impl Default for EvaluatorOptions {
+    fn default() -> Self {
+        Self {
+            default_fields: vec!["title".to_string()],
+            functions: FunctionRegistry::default(),
+            analyzers: HashMap::new(),
+            default_analyzer: Arc::new(TextAnalyzer::standard()),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use super::document::is_truthy;
+use super::scope::Scope;
+use super::{Evaluator, EvaluatorError};
+use crate::ast::statement::Statement;
+use crate::ast::AST;
+use crate::tokenizer::token::Span;
+use serde_json::Value;
+
+/// Evaluation of a node of a query against a document.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// Position of the node in the query.
+    pub span: Span,
+    pub value: Value,
+    /// If the value is truthy.
+    pub matched: bool,
+    /// Conditions of `AND`, `OR`, `NOT` and boosts, all evaluated even when
+    /// the first ones decide the result.
+    pub conditions: Vec<Explanation>,
+    /// Values a condition compares or passes to a function, like `brand`
+    /// and `"acme"` for `brand:"acme"`.
+    pub operands: Vec<Explanation>,
+}
+
+impl Explanation {
+    /// Conditions that make the query fail: the failing conditions of a
+    /// failing `AND` or `OR` and, for a failing comparison or `NOT`, itself.
+    /// Empty when the query matches.
+    pub fn failures(&self) -> Vec<&Explanation> {
+        if self.matched {
+            return vec![];
+        }
+        let failing: Vec<&Explanation> = self
+            .conditions
+            .iter()
+            .filter(|condition| !condition.matched)
+            .collect();
+        match failing.is_empty() {
+            true => vec![self],
+            false => failing
+                .into_iter()
+                .flat_map(|condition| condition.failures())
+                .collect(),
+        }
+    }
+
+    /// The query with each line followed by `^` under its failures.
+    ///
+    /// ```text
+    /// brand:acme price > 10
+    ///            ^^^^^^^^^^
+    /// ```
+    pub fn underline(&self, source: &str) -> String {
+        let failures = self.failures();
+        let mut lines = vec![];
+        let mut offset = 0;
+        for line in source.split('\n') {
+            let length = line.chars().count();
+            let marks: String = (offset..offset + length)
+                .map(|position| {
+                    let failed = failures.iter().any(|failure| {
+                        failure.span.start <= position && position < failure.span.end
+                    });
+                    if failed {
+                        '^'
+                    } else {
+                        ' '
+                    }
+                })
+                .collect();
+            lines.push(line.to_string());
+            if !marks.trim_end().is_empty() {
+                lines.push(marks.trim_end().to_string());
+            }
+            offset += length + 1;
+        }
+        lines.join("\n")
+    }
+}
+
+impl Evaluator {
+    /// Evaluate every node of the query against `document`, to tell why it
+    /// matches or not. The stages of a pipeline are ignored.
+    pub fn explain(&self, ast: &AST, document: &Value) -> Result<Explanation, EvaluatorError> {
+        let statement = match ast.statement() {
+            Statement::Pipeline { source, .. } => source,
+            statement => statement,
+        };
+        self.explain_statement(statement, document, &Scope::default())
+    }
+
+    fn explain_statement(
+        &self,
+        statement: &Statement,
+        document: &Value,
+        scope: &Scope,
+    ) -> Result<Explanation, EvaluatorError> {
+        let mut conditions = vec![];
+        let mut operands = vec![];
+        let value = match statement {
+            Statement::Expression { expresssion, .. } => {
+                for statement in expresssion {
+                    conditions.push(self.explain_statement(statement, document, scope)?);
+                }
+                Value::Bool(conditions.iter().all(|condition| condition.matched))
+            }
+            Statement::BinaryExpression {
+                left,
+                operator,
+                right,
+                ..
+            } if operator == "&&" || operator == "||" => {
+                let left = self.explain_statement(left, document, scope)?;
+                let right = self.explain_statement(right, document, scope)?;
+                let matched = match operator.as_str() {
+                    "&&" => left.matched && right.matched,
+                    _ => left.matched || right.matched,
+                };
+                conditions.extend([left, right]);
+                Value::Bool(matched)
+            }
+            Statement::Not { argument, .. } => {
+                let argument = self.explain_statement(argument, document, scope)?;
+                let matched = !argument.matched;
+                conditions.push(argument);
+                Value::Bool(matched)
+            }
+            Statement::Boost { argument, .. } => {
+                let argument = self.explain_statement(argument, document, scope)?;
+                let value = argument.value.clone();
+                conditions.push(argument);
+                value
+            }
+            Statement::BinaryExpression { left, right, .. } => {
+                operands.push(self.explain_statement(left, document, scope)?);
+                match &**right {
+                    // Matched against the left side, not values on their own.
+                    Statement::Wildcard { .. }
+                    | Statement::Fuzzy { .. }
+                    | Statement::Phrase { .. } => {}
+                    Statement::RangeExpression { start, end, .. } => {
+                        for bound in start.iter().chain(end) {
+                            operands.push(self.explain_statement(bound, document, scope)?);
+                        }
+                    }
+                    right => operands.push(self.explain_statement(right, document, scope)?),
+                }
+                self.evaluate(statement, document, scope)?
+            }
+            Statement::Call { arguments, .. } => {
+                for argument in arguments {
+                    operands.push(self.explain_statement(argument, document, scope)?);
+                }
+                self.evaluate(statement, document, scope)?
+            }
+            statement => self.evaluate(statement, document, scope)?,
+        };
+
+        Ok(Explanation {
+            span: statement.clone().to_span(),
+            matched: is_truthy(&value),
+            value,
+            conditions,
+            operands,
+        })
+    }
+}
@@ -0,0 +1,19 @@
+#[derive(Debug, Clone, Default)]
+pub struct FacetOptions {
+    /// Fields whose values are counted, paths like `brand` or `specs.color`.
+    pub fields: Vec<String>,
+    /// Count each field with the conditions on that same field excluded, so
+    /// selecting `brand:acme` still shows the counts of the other brands.
+    pub exclude_own_conditions: bool,
+}
+
+impl FacetOptions {
+    pub fn set_fields<A: ToString>(&mut self, fields: Vec<A>) -> &mut Self {
+        self.fields = fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+    pub fn set_exclude_own_conditions(&mut self, exclude_own_conditions: bool) -> &mut Self {
+        self.exclude_own_conditions = exclude_own_conditions;
+        self
+    }
+}
@@ -0,0 +1,239 @@
+use super::document::{elements, is_truthy, lookup, value_cmp, value_key};
+use super::scope::Scope;
+use super::{strip_root, Evaluator, EvaluatorError, FacetOptions};
+use crate::ast::statement::Statement;
+use crate::ast::AST;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Documents matching a query and the counts of the values of each field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Facets {
+    pub documents: Vec<Value>,
+    /// In the order of [`FacetOptions::fields`].
+    pub facets: Vec<Facet>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Facet {
+    pub field: String,
+    /// Most frequent first, ties ordered like `sort`.
+    pub values: Vec<FacetValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetValue {
+    pub value: Value,
+    pub count: usize,
+}
+
+impl Evaluator {
+    /// Matching documents and facet counts, in a single pass.
+    ///
+    /// A document counts once for each distinct value of a field, every
+    /// element of an array is a value and `null` isn't. Each condition of
+    /// the query is evaluated once per document, so a facet excluding its
+    /// own conditions is the conjunction of the others, the conditions being
+    /// those joined by spaces, `AND` or `&&`. A condition belongs
+    /// to a field when every path it uses is that field, like `brand:acme`
+    /// or `(brand:acme OR brand:bolt)`. The stages of a pipeline are ignored.
+    pub fn facets<I>(
+        &self,
+        ast: &AST,
+        documents: I,
+        options: &FacetOptions,
+    ) -> Result<Facets, EvaluatorError>
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        let statement = match ast.statement() {
+            Statement::Pipeline { source, .. } => source,
+            statement => statement,
+        };
+        let mut conditions = vec![];
+        split_conditions(statement, &mut conditions);
+        let fields: Vec<Vec<String>> = options
+            .fields
+            .iter()
+            .map(|field| field.split('.').map(|s| s.to_string()).collect())
+            .collect();
+        // Conditions each facet takes into account.
+        let relevant: Vec<Vec<bool>> = fields
+            .iter()
+            .map(|field| {
+                conditions
+                    .iter()
+                    .map(|condition| {
+                        !options.exclude_own_conditions || !self.is_condition_on(condition, field)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let scope = Scope::default();
+        let mut matches = vec![];
+        let mut counts: Vec<(Vec<FacetValue>, HashMap<String, usize>)> =
+            vec![Default::default(); fields.len()];
+        for document in documents {
+            let mut results = vec![];
+            for condition in &conditions {
+                results.push(is_truthy(&self.evaluate(condition, &document, &scope)?));
+            }
+
+            for (index, field) in fields.iter().enumerate() {
+                let counted = results
+                    .iter()
+                    .zip(&relevant[index])
+                    .all(|(result, relevant)| *result || !relevant);
+                if !counted {
+                    continue;
+                }
+                let (values, positions) = &mut counts[index];
+                let mut seen = vec![];
+                for value in elements(lookup(&document, field)) {
+                    let key = value_key(&value);
+                    if seen.contains(&key) {
+                        continue;
+                    }
+                    match positions.get(&key) {
+                        Some(&position) => values[position].count += 1,
+                        None => {
+                            positions.insert(key.clone(), values.len());
+                            values.push(FacetValue { value, count: 1 });
+                        }
+                    }
+                    seen.push(key);
+                }
+            }
+
+            if results.iter().all(|result| *result) {
+                matches.push(document);
+            }
+        }
+
+        let facets = options
+            .fields
+            .iter()
+            .zip(counts)
+            .map(|(field, (mut values, _))| {
+                values.sort_by(|left, right| {
+                    right
+                        .count
+                        .cmp(&left.count)
+                        .then_with(|| value_cmp(&left.value, &right.value))
+                });
+                Facet {
+                    field: field.clone(),
+                    values,
+                }
+            })
+            .collect();
+
+        Ok(Facets {
+            documents: matches,
+            facets,
+        })
+    }
+
+    fn is_condition_on(&self, condition: &Statement, field: &[String]) -> bool {
+        let mut paths = vec![];
+        self.paths(condition, &mut paths);
+        !paths.is_empty() && paths.iter().all(|path| path == field)
+    }
+
+    /// Paths a statement reads, free text reads the default fields.
+    fn paths(&self, statement: &Statement, paths: &mut Vec<Vec<String>>) {
+        match statement {
+            Statement::Identifier { .. } | Statement::MemberExpression { .. } => {
+                paths.extend(statement.to_path().map(strip_root))
+            }
+            Statement::Text { .. }
+            | Statement::Wildcard { .. }
+            | Statement::Fuzzy { .. }
+            | Statement::Phrase { .. } => paths.extend(
+                self.options
+                    .default_fields
+                    .iter()
+                    .map(|field| field.split('.').map(|s| s.to_string()).collect()),
+            ),
+            Statement::Expression { expresssion, .. } => {
+                for statement in expresssion {
+                    self.paths(statement, paths);
+                }
+            }
+            Statement::BinaryExpression { left, right, .. } => {
+                self.paths(left, paths);
+                // The value of `brand:ac*` is matched against `brand`.
+                if !matches!(
+                    **right,
+                    Statement::Wildcard { .. } | Statement::Fuzzy { .. } | Statement::Phrase { .. }
+                ) {
+                    self.paths(right, paths);
+                }
+            }
+            Statement::Not { argument, .. } | Statement::Boost { argument, .. } => {
+                self.paths(argument, paths)
+            }
+            Statement::ArrayExpression { elements, .. } => {
+                for statement in elements {
+                    self.paths(statement, paths);
+                }
+            }
+            Statement::RangeExpression { start, end, .. } => {
+                for statement in start.iter().chain(end) {
+                    self.paths(statement, paths);
+                }
+            }
+            Statement::Call { arguments, .. } => {
+                for statement in arguments {
+                    self.paths(statement, paths);
+                }
+            }
+            Statement::Quantifier {
+                collection,
+                predicate,
+                ..
+            } => {
+                for statement in collection.iter().chain([predicate]) {
+                    self.paths(statement, paths);
+                }
+            }
+            Statement::ObjectExpression { properties, .. } => {
+                for (_, statement) in properties {
+                    self.paths(statement, paths);
+                }
+            }
+            Statement::TemplateLiteral { expressions, .. } => {
+                for statement in expressions {
+                    self.paths(statement, paths);
+                }
+            }
+            Statement::Literal { .. }
+            | Statement::Select { .. }
+            | Statement::Pipeline { .. }
+            | Statement::Aggregate { .. } => {}
+        }
+    }
+}
+
+/// Conditions of a conjunction, `brand:acme price > 5`,
+/// `brand:acme AND price > 5` or `brand:acme && price > 5`.
+fn split_conditions<'a>(statement: &'a Statement, conditions: &mut Vec<&'a Statement>) {
+    match statement {
+        Statement::Expression { expresssion, .. } => {
+            for statement in expresssion {
+                split_conditions(statement, conditions);
+            }
+        }
+        Statement::BinaryExpression {
+            left,
+            operator,
+            right,
+            ..
+        } if operator == "&&" => {
+            split_conditions(left, conditions);
+            split_conditions(right, conditions);
+        }
+        statement => conditions.push(statement),
+    }
+}
@@ -0,0 +1,158 @@
+use super::{strip_root, Evaluator, EvaluatorError};
+use crate::ast::statement::Statement;
+use crate::ast::AST;
+use crate::queryable::Queryable;
+
+/// Names bound by `any(tags, t => ...)` with the path of their collection,
+/// `None` when the collection isn't a path.
+type Bindings = Vec<(String, Option<Vec<String>>)>;
+
+impl Evaluator {
+    /// Check that every path of the query is a field of `D`, see
+    /// [`Queryable::has_field`], and so are the default fields when the
+    /// query has free text. The stages of a pipeline are ignored.
+    pub fn check_fields<D: Queryable + ?Sized>(&self, ast: &AST) -> Result<(), EvaluatorError> {
+        let statement = match ast.statement() {
+            Statement::Pipeline { source, .. } => source,
+            statement => statement,
+        };
+        let mut checker = Checker {
+            default_fields: &self.options.default_fields,
+            bindings: vec![],
+        };
+        checker.check::<D>(statement)
+    }
+}
+
+struct Checker<'a> {
+    /// Fields of the free text.
+    default_fields: &'a [String],
+    bindings: Bindings,
+}
+
+impl Checker<'_> {
+    fn check<D: Queryable + ?Sized>(
+        &mut self,
+        statement: &Statement,
+    ) -> Result<(), EvaluatorError> {
+        match statement {
+            Statement::Identifier { span, .. } | Statement::MemberExpression { span, .. } => {
+                let path = statement.to_path().unwrap_or_default();
+                match bind(path, &self.bindings) {
+                    Some(path) if !D::has_field(&path) => {
+                        Err(EvaluatorError::UnknownField(path.join("."), span.clone()))
+                    }
+                    _ => Ok(()),
+                }
+            }
+            Statement::Expression { expresssion, .. } => expresssion
+                .iter()
+                .try_for_each(|statement| self.check::<D>(statement)),
+            Statement::Not { argument, .. } | Statement::Boost { argument, .. } => {
+                self.check::<D>(argument)
+            }
+            // The wildcard, fuzzy term or phrase of `field:value` is tested
+            // against the field, not the default fields.
+            Statement::BinaryExpression {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                self.check::<D>(left)?;
+                match (operator.as_str(), &**right) {
+                    (
+                        "=" | ":",
+                        Statement::Wildcard { .. }
+                        | Statement::Fuzzy { .. }
+                        | Statement::Phrase { .. },
+                    ) => Ok(()),
+                    (_, right) => self.check::<D>(right),
+                }
+            }
+            Statement::RangeExpression { start, end, .. } => start
+                .iter()
+                .chain(end)
+                .try_for_each(|bound| self.check::<D>(bound)),
+            Statement::ArrayExpression {
+                elements: statements,
+                ..
+            }
+            | Statement::TemplateLiteral {
+                expressions: statements,
+                ..
+            }
+            | Statement::Call {
+                arguments: statements,
+                ..
+            } => statements
+                .iter()
+                .try_for_each(|statement| self.check::<D>(statement)),
+            Statement::ObjectExpression { properties, .. } => properties
+                .iter()
+                .try_for_each(|(_, value)| self.check::<D>(value)),
+            Statement::Quantifier {
+                collection,
+                binding,
+                predicate,
+                ..
+            } => {
+                if let Some(collection) = collection {
+                    self.check::<D>(collection)?;
+                }
+                match (collection, binding) {
+                    (Some(collection), Some(binding)) => {
+                        let path = collection
+                            .to_path()
+                            .and_then(|path| bind(path, &self.bindings));
+                        self.bindings.push((binding.clone(), path));
+                        let result = self.check::<D>(predicate);
+                        self.bindings.pop();
+                        result
+                    }
+                    _ => self.check::<D>(predicate),
+                }
+            }
+            Statement::Text { span, .. }
+            | Statement::Wildcard { span, .. }
+            | Statement::Fuzzy { span, .. }
+            | Statement::Phrase { span, .. } => {
+                match self
+                    .default_fields
+                    .iter()
+                    .find(|field| !D::has_field(&path(field)))
+                {
+                    Some(field) => Err(EvaluatorError::UnknownField(field.clone(), span.clone())),
+                    None => Ok(()),
+                }
+            }
+            Statement::Literal { .. }
+            | Statement::Select { .. }
+            | Statement::Pipeline { .. }
+            | Statement::Aggregate { .. } => Ok(()),
+        }
+    }
+}
+
+/// Path of a default field, split at its dots.
+fn path(field: &str) -> Vec<String> {
+    field.split('.').map(|s| s.to_string()).collect()
+}
+
+/// Path from the root of the document, replacing a bound name by the path of
+/// its collection.
+fn bind(path: Vec<String>, bindings: &Bindings) -> Option<Vec<String>> {
+    let path = strip_root(path);
+    match bindings
+        .iter()
+        .rev()
+        .find(|(name, _)| path.first() == Some(name))
+    {
+        Some((_, collection)) => {
+            let mut bound = collection.clone()?;
+            bound.extend_from_slice(&path[1..]);
+            Some(bound)
+        }
+        None => Some(path),
+    }
+}
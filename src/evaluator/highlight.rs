@@ -0,0 +1,367 @@
+use super::document::{edit_distance, glob_matches, lookup, phrase_spans};
+use super::scope::Scope;
+use super::{strip_root, Evaluator, EvaluatorError, SnippetOptions};
+use crate::analyzer::Analyzer;
+use crate::ast::statement::Statement;
+use crate::ast::value::Value as AstValue;
+use crate::ast::AST;
+use crate::tokenizer::token::Span;
+use serde_json::Value;
+use std::ops::Range;
+
+/// Character ranges of a string satisfying a term.
+type Ranges<'a> = dyn Fn(&str) -> Vec<Range<usize>> + 'a;
+/// Character ranges of a string of a default field, with its analyzer.
+type AnalyzedRanges<'a> = dyn Fn(&str, &dyn Analyzer) -> Vec<Range<usize>> + 'a;
+
+/// Parts of a string value of a document satisfying the terms of a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    /// Path of the string, with the index of each array walked through like
+    /// `tags.1` or `variants.0.name`.
+    pub field: String,
+    /// Character ranges, sorted and merged when they overlap.
+    pub ranges: Vec<Range<usize>>,
+    /// Ranges of the string satisfying each term, in the order of the
+    /// terms in the query.
+    pub terms: Vec<TermHighlight>,
+}
+
+/// Parts of a string satisfying one term of a query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermHighlight {
+    /// Position of the term in the query, like `rac*` or `brand:Ac*`.
+    pub span: Span,
+    /// Character ranges, sorted, they may overlap those of other terms.
+    pub ranges: Vec<Range<usize>>,
+}
+
+impl Highlight {
+    /// Fragment of the field of `document` around the first range, with the
+    /// ranges wrapped in the markers of the options.
+    pub fn snippet(&self, document: &Value, options: &SnippetOptions) -> String {
+        let path: Vec<String> = self.field.split('.').map(|s| s.to_string()).collect();
+        let text = match lookup(document, &path) {
+            Value::String(text) => text,
+            _ => return String::new(),
+        };
+        let chars: Vec<char> = text.chars().collect();
+        let (start, end) = match (self.ranges.first(), options.max_length) {
+            (_, 0) => (0, chars.len()),
+            _ if chars.len() <= options.max_length => (0, chars.len()),
+            (None, max_length) => (0, max_length),
+            // Center the first range, or start with it when it doesn't fit.
+            (Some(first), max_length) => {
+                let margin = max_length.saturating_sub(first.len()) / 2;
+                let start = first
+                    .start
+                    .saturating_sub(margin)
+                    .min(chars.len() - max_length);
+                (start, start + max_length)
+            }
+        };
+
+        let mut snippet = String::new();
+        if start > 0 {
+            snippet.push_str(&options.ellipsis);
+        }
+        let mut position = start;
+        for range in &self.ranges {
+            let from = range.start.clamp(start, end);
+            let to = range.end.clamp(start, end);
+            if from == to {
+                continue;
+            }
+            snippet.extend(&chars[position..from]);
+            snippet.push_str(&options.pre_tag);
+            snippet.extend(&chars[from..to]);
+            snippet.push_str(&options.post_tag);
+            position = to;
+        }
+        snippet.extend(&chars[position..end]);
+        if end < chars.len() {
+            snippet.push_str(&options.ellipsis);
+        }
+        snippet
+    }
+}
+
+impl Evaluator {
+    /// Strings of a document satisfying the terms of the query, `None` when
+    /// the document doesn't match.
+    ///
+    /// Free text highlights the default fields: a text where it appears, a
+    /// wildcard or fuzzy term the words it matches and a phrase its words
+    /// and those in between. A condition on a field highlights its strings
+    /// as a whole, the words of a phrase or the matches of a regex. Terms
+    /// under a negation or a quantifier aren't highlighted, nor numbers.
+    pub fn highlight(
+        &self,
+        ast: &AST,
+        document: &Value,
+    ) -> Result<Option<Vec<Highlight>>, EvaluatorError> {
+        if !self.test(ast, document)? {
+            return Ok(None);
+        }
+        let statement = match ast.statement() {
+            Statement::Pipeline { source, .. } => source,
+            statement => statement,
+        };
+        let mut highlights = vec![];
+        self.collect_highlights(statement, document, &mut highlights)?;
+        for highlight in &mut highlights {
+            highlight.ranges = merge(
+                highlight
+                    .terms
+                    .iter()
+                    .flat_map(|term| term.ranges.iter().cloned())
+                    .collect(),
+            );
+        }
+        Ok(Some(highlights))
+    }
+
+    fn collect_highlights(
+        &self,
+        statement: &Statement,
+        document: &Value,
+        highlights: &mut Vec<Highlight>,
+    ) -> Result<(), EvaluatorError> {
+        match statement {
+            Statement::Expression { expresssion, .. } => {
+                for statement in expresssion {
+                    self.collect_highlights(statement, document, highlights)?;
+                }
+            }
+            Statement::Boost { argument, .. } => {
+                self.collect_highlights(argument, document, highlights)?
+            }
+            Statement::Text { span, value } => {
+                let text = match value {
+                    AstValue::String(text) => text.clone(),
+                    value => Value::from(value).to_string(),
+                };
+                self.highlight_free_text(document, span, highlights, &|text_value, analyzer| {
+                    text_ranges(text_value, analyzer, &text)
+                });
+            }
+            Statement::Wildcard { span, pattern } => {
+                self.highlight_free_text(document, span, highlights, &|text, analyzer| {
+                    let pattern = analyzer.normalize(pattern);
+                    word_ranges(text, analyzer, &|word| glob_matches(&pattern, word))
+                })
+            }
+            Statement::Fuzzy {
+                span,
+                term,
+                distance,
+            } => self.highlight_free_text(document, span, highlights, &|text, analyzer| {
+                let term = analyzer.normalize(term);
+                word_ranges(text, analyzer, &|word| {
+                    edit_distance(&term, word, *distance).is_some()
+                })
+            }),
+            Statement::Phrase { span, phrase, slop } => {
+                self.highlight_free_text(document, span, highlights, &|text, analyzer| {
+                    phrase_spans(text, analyzer, &analyzer.analyze(phrase), *slop)
+                        .into_iter()
+                        .map(|range| char_range(text, range))
+                        .collect()
+                })
+            }
+            Statement::BinaryExpression {
+                span,
+                left,
+                operator,
+                right,
+            } => match operator.as_str() {
+                "&&" | "||" => {
+                    self.collect_highlights(left, document, highlights)?;
+                    self.collect_highlights(right, document, highlights)?;
+                }
+                "=" | ":" | "=~" => {
+                    let Some(path) = left.to_path().map(strip_root) else {
+                        return Ok(());
+                    };
+                    let test: Box<Ranges> = match (operator.as_str(), &**right) {
+                        ("=~", right) => {
+                            let regex = self.regex(right, document, &Scope::default())?;
+                            Box::new(move |text| {
+                                regex
+                                    .find_iter(text)
+                                    .map(|found| char_range(text, found.range()))
+                                    .collect()
+                            })
+                        }
+                        (_, Statement::Wildcard { pattern, .. }) => {
+                            Box::new(move |text| whole(text, glob_matches(pattern, text)))
+                        }
+                        (_, Statement::Fuzzy { term, distance, .. }) => Box::new(move |text| {
+                            whole(text, edit_distance(term, text, *distance).is_some())
+                        }),
+                        (_, Statement::Phrase { phrase, slop, .. }) => {
+                            let analyzer = self.options.analyzer(&path.join("."));
+                            let phrase = analyzer.analyze(phrase);
+                            Box::new(move |text| {
+                                phrase_spans(text, analyzer, &phrase, *slop)
+                                    .into_iter()
+                                    .map(|range| char_range(text, range))
+                                    .collect()
+                            })
+                        }
+                        (_, Statement::Literal { value, .. }) => match Value::from(value) {
+                            Value::String(value) => {
+                                Box::new(move |text| whole(text, text == value))
+                            }
+                            _ => return Ok(()),
+                        },
+                        _ => return Ok(()),
+                    };
+                    let term = (span, &*test);
+                    highlight_strings(document, &path, String::new(), term, highlights);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn highlight_free_text(
+        &self,
+        document: &Value,
+        span: &Span,
+        highlights: &mut Vec<Highlight>,
+        test: &AnalyzedRanges,
+    ) {
+        for field in &self.options.default_fields {
+            let path: Vec<String> = field.split('.').map(|s| s.to_string()).collect();
+            let analyzer = self.options.analyzer(field);
+            highlight_strings(
+                document,
+                &path,
+                String::new(),
+                (span, &|text| test(text, analyzer)),
+                highlights,
+            );
+        }
+    }
+}
+
+/// Add the ranges of the term, its span and test, of each string at `path`,
+/// walking arrays element by element like [`lookup`] does.
+fn highlight_strings(
+    value: &Value,
+    path: &[String],
+    field: String,
+    term: (&Span, &Ranges),
+    highlights: &mut Vec<Highlight>,
+) {
+    let (span, test) = term;
+    let join = |segment: &str| match field.is_empty() {
+        true => segment.to_string(),
+        false => format!("{field}.{segment}"),
+    };
+    match (value, path.split_first()) {
+        (Value::String(text), None) => {
+            let mut ranges = test(text);
+            if ranges.is_empty() {
+                return;
+            }
+            ranges.sort_by_key(|range| (range.start, range.end));
+            let term = TermHighlight {
+                span: span.clone(),
+                ranges,
+            };
+            match highlights
+                .iter_mut()
+                .find(|highlight| highlight.field == field)
+            {
+                Some(highlight) => highlight.terms.push(term),
+                None => highlights.push(Highlight {
+                    field,
+                    ranges: vec![],
+                    terms: vec![term],
+                }),
+            }
+        }
+        (Value::Array(items), Some((segment, rest))) if segment.parse::<usize>().is_ok() => {
+            if let Some(item) = items.get(segment.parse::<usize>().unwrap()) {
+                highlight_strings(item, rest, join(segment), term, highlights);
+            }
+        }
+        (Value::Array(items), _) => {
+            for (index, item) in items.iter().enumerate() {
+                highlight_strings(item, path, join(&index.to_string()), term, highlights);
+            }
+        }
+        (Value::Object(map), Some((segment, rest))) => {
+            if let Some(value) = map.get(segment) {
+                highlight_strings(value, rest, join(segment), term, highlights);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Character ranges of the normalized text in the normalized value.
+///
+/// Each character is normalized on its own to map the normalized value back
+/// to the characters it comes from.
+fn text_ranges(value: &str, analyzer: &dyn Analyzer, text: &str) -> Vec<Range<usize>> {
+    let text = analyzer.normalize(text);
+    if text.is_empty() {
+        return vec![];
+    }
+    let mut normalized = String::new();
+    // Character of the value each byte of `normalized` comes from.
+    let mut origins = vec![];
+    for (index, c) in value.chars().enumerate() {
+        let c = analyzer.normalize(&c.to_string());
+        origins.extend(std::iter::repeat_n(index, c.len()));
+        normalized.push_str(&c);
+    }
+    normalized
+        .match_indices(&text)
+        .map(|(start, found)| origins[start]..origins[start + found.len() - 1] + 1)
+        .collect()
+}
+
+/// Character ranges of the words whose normalized form passes the test.
+fn word_ranges(
+    text: &str,
+    analyzer: &dyn Analyzer,
+    test: &dyn Fn(&str) -> bool,
+) -> Vec<Range<usize>> {
+    analyzer
+        .token_ranges(text)
+        .into_iter()
+        .filter(|range| test(&analyzer.normalize(&text[range.clone()])))
+        .map(|range| char_range(text, range))
+        .collect()
+}
+
+fn whole(text: &str, matches: bool) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    if matches {
+        ranges.push(0..text.chars().count());
+    }
+    ranges
+}
+
+fn char_range(text: &str, bytes: Range<usize>) -> Range<usize> {
+    let start = text[..bytes.start].chars().count();
+    start..start + text[bytes].chars().count()
+}
+
+fn merge(mut ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    ranges.sort_by_key(|range| (range.start, range.end));
+    let mut merged: Vec<Range<usize>> = vec![];
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
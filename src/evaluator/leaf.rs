@@ -0,0 +1,90 @@
+use super::document::{any_string, any_word, edit_distance, glob_matches, phrase_matches, texts};
+use crate::analyzer::Analyzer;
+use crate::ast::statement::Statement;
+use crate::ast::value::Value as AstValue;
+use serde_json::Value;
+
+/// A term of the query prepared once for the analyzer of a field, shared by
+/// [`super::Evaluator`], [`super::CompiledQuery`] and [`crate::bytecode::Vm`].
+///
+/// Free text, wildcards and fuzzy terms are normalized and phrases analyzed
+/// like the words of the field. `brand:ac*` and `brand:acme~1` test whole
+/// strings as written instead.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Leaf {
+    /// Normalized text contained in the normalized text of the field.
+    Text(String),
+    /// Glob matching a normalized word.
+    Wildcard(String),
+    /// Normalized term within that many edits of a normalized word.
+    Fuzzy(String, usize),
+    /// Analyzed terms in order, with at most that many other terms between.
+    Phrase(Vec<String>, usize),
+    /// Glob matching a whole string.
+    Glob(String),
+    /// Term within that many edits of a whole string.
+    FuzzyString(String, usize),
+}
+
+impl Leaf {
+    /// Leaf of free text, `laptop`, `lapt*`, `colr~` or `"red bag"`, for a
+    /// default field.
+    pub(crate) fn free_text(statement: &Statement, analyzer: &dyn Analyzer) -> Option<Self> {
+        match statement {
+            Statement::Text { value, .. } => Some(Leaf::Text(analyzer.normalize(&text(value)))),
+            Statement::Wildcard { pattern, .. } => {
+                Some(Leaf::Wildcard(analyzer.normalize(pattern)))
+            }
+            Statement::Fuzzy { term, distance, .. } => {
+                Some(Leaf::Fuzzy(analyzer.normalize(term), *distance))
+            }
+            Statement::Phrase { phrase, slop, .. } => {
+                Some(Leaf::Phrase(analyzer.analyze(phrase), *slop))
+            }
+            _ => None,
+        }
+    }
+
+    /// Leaf of the value of `field:value`, a wildcard, a fuzzy term or a
+    /// phrase.
+    pub(crate) fn field(statement: &Statement, analyzer: &dyn Analyzer) -> Option<Self> {
+        match statement {
+            Statement::Wildcard { pattern, .. } => Some(Leaf::Glob(pattern.clone())),
+            Statement::Fuzzy { term, distance, .. } => {
+                Some(Leaf::FuzzyString(term.clone(), *distance))
+            }
+            Statement::Phrase { phrase, slop, .. } => {
+                Some(Leaf::Phrase(analyzer.analyze(phrase), *slop))
+            }
+            _ => None,
+        }
+    }
+
+    /// Test a value of the field with the analyzer the leaf was prepared for.
+    pub(crate) fn matches(&self, value: &Value, analyzer: &dyn Analyzer) -> bool {
+        match self {
+            Leaf::Text(text) => texts(value)
+                .iter()
+                .any(|value| analyzer.normalize(value).contains(text.as_str())),
+            Leaf::Wildcard(pattern) => {
+                any_word(value, analyzer, &|word| glob_matches(pattern, word))
+            }
+            Leaf::Fuzzy(term, distance) => any_word(value, analyzer, &|word| {
+                edit_distance(term, word, *distance).is_some()
+            }),
+            Leaf::Phrase(phrase, slop) => phrase_matches(value, analyzer, phrase, *slop),
+            Leaf::Glob(pattern) => any_string(value, |text| glob_matches(pattern, text)),
+            Leaf::FuzzyString(term, distance) => {
+                any_string(value, |text| edit_distance(term, text, *distance).is_some())
+            }
+        }
+    }
+}
+
+/// Text of free text, a number as written.
+fn text(value: &AstValue) -> String {
+    match value {
+        AstValue::String(text) => text.clone(),
+        value => Value::from(value).to_string(),
+    }
+}
@@ -0,0 +1,116 @@
+use super::pipeline::{sort_values_cmp, Stream};
+use super::{Cursor, Evaluator, EvaluatorError};
+use crate::ast::statement::Statement;
+use crate::ast::{Stage, AST};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Documents of a page and the cursor of the next one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub documents: Vec<Value>,
+    /// `None` on the last page, or if the pipeline has no `sort` or `limit`.
+    pub cursor: Option<Cursor>,
+}
+
+impl Evaluator {
+    /// Page of `status:open | sort price, id | limit 10` after `cursor`.
+    ///
+    /// Documents up to the cursor are dropped as they are read, before
+    /// sorting, so earlier pages aren't sorted again. The cursor follows the
+    /// keys of the `sort` stages, which should end with a unique field so
+    /// ties don't cross a page boundary, `pick` can't come before a
+    /// `sort` and there can't be a `group`. An `offset` skips documents after the cursor.
+    pub fn page<'a, I>(
+        &'a self,
+        ast: &'a AST,
+        documents: I,
+        cursor: Option<&'a Cursor>,
+    ) -> Result<Page, EvaluatorError>
+    where
+        I: IntoIterator<Item = Value>,
+        I::IntoIter: 'a,
+    {
+        let (source, stages) = match ast.statement() {
+            Statement::Pipeline { source, stages, .. } => (&**source, &stages[..]),
+            Statement::Select { span, .. } => {
+                return Err(EvaluatorError::StatementNotSupported(span.clone()))
+            }
+            statement => (statement, &[][..]),
+        };
+
+        // Sorting again by other keys keeps the previous order for ties, so
+        // the keys of the last `sort` go first.
+        let mut keys = vec![];
+        let mut picks = vec![];
+        for stage in stages {
+            match stage {
+                Stage::Sort { span, .. } if !picks.is_empty() => {
+                    return Err(EvaluatorError::StatementNotSupported(span.clone()))
+                }
+                Stage::Sort {
+                    keys: stage_keys, ..
+                } => {
+                    let mut stage_keys = stage_keys.clone();
+                    stage_keys.append(&mut keys);
+                    keys = stage_keys;
+                }
+                Stage::Pick { .. } => picks.push(stage),
+                Stage::Group { span, .. } => {
+                    return Err(EvaluatorError::StatementNotSupported(span.clone()))
+                }
+                _ => {}
+            }
+        }
+        let limit = stages
+            .iter()
+            .filter_map(|stage| match stage {
+                Stage::Limit { count, .. } => Some(*count),
+                _ => None,
+            })
+            .min();
+
+        let mut stream: Stream<'a> = self.source(source, documents);
+        if let Some(cursor) = cursor {
+            if cursor.values.len() != keys.len() {
+                return Err(EvaluatorError::InvalidCursor(cursor.encode()));
+            }
+            let keys = keys.clone();
+            stream = Box::new(stream.filter_map(move |document| {
+                let after = document.as_ref().map_or(Ok(true), |document| {
+                    self.sort_values(&keys, document).map(|values| {
+                        sort_values_cmp(&keys, &values, &cursor.values) == Ordering::Greater
+                    })
+                });
+                match after {
+                    Ok(true) => Some(document),
+                    Ok(false) => None,
+                    Err(error) => Some(Err(error)),
+                }
+            }));
+        }
+        for stage in stages {
+            if !matches!(stage, Stage::Pick { .. }) {
+                stream = self.stage(stage, stream);
+            }
+        }
+        let documents = stream.collect::<Result<Vec<_>, _>>()?;
+
+        let cursor = match (documents.last(), limit) {
+            (Some(last), Some(limit)) if !keys.is_empty() && documents.len() == limit => {
+                Some(Cursor::new(self.sort_values(&keys, last)?))
+            }
+            _ => None,
+        };
+
+        let mut stream: Stream<'a> = Box::new(documents.into_iter().map(Ok));
+        for stage in picks {
+            stream = self.stage(stage, stream);
+        }
+
+        Ok(Page {
+            documents: stream.collect::<Result<Vec<_>, _>>()?,
+            cursor,
+        })
+    }
+}
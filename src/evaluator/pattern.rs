@@ -0,0 +1,56 @@
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Compile the pattern of a regex literal with its flags, `i`, `m`, `s` and
+/// `u`, which is the default.
+pub(crate) fn compile_regex(pattern: &str, flags: &str) -> Result<Regex, String> {
+    let mut builder = RegexBuilder::new(pattern);
+    for flag in flags.chars() {
+        match flag {
+            'i' => builder.case_insensitive(true),
+            'm' => builder.multi_line(true),
+            's' => builder.dot_matches_new_line(true),
+            'u' => builder.unicode(true),
+            flag => return Err(format!("flag not supported {:?}", flag)),
+        };
+    }
+    builder.build().map_err(|error| error.to_string())
+}
+
+/// Regexes compiled by flags and pattern, for the patterns only known when
+/// a document is evaluated, like the right side of `name =~ pattern`.
+///
+/// The lock is only held to look a regex up, which doesn't allocate, and the
+/// cache is emptied once it holds [`RegexCache::CAPACITY`] regexes.
+#[derive(Debug, Default)]
+pub(crate) struct RegexCache {
+    regexes: Mutex<HashMap<String, HashMap<String, Arc<Regex>>>>,
+}
+
+impl RegexCache {
+    const CAPACITY: usize = 256;
+
+    pub(crate) fn get(&self, pattern: &str, flags: &str) -> Result<Arc<Regex>, String> {
+        let regex = self
+            .regexes
+            .lock()
+            .unwrap()
+            .get(flags)
+            .and_then(|regexes| regexes.get(pattern))
+            .cloned();
+        if let Some(regex) = regex {
+            return Ok(regex);
+        }
+        let regex = Arc::new(compile_regex(pattern, flags)?);
+        let mut regexes = self.regexes.lock().unwrap();
+        if regexes.values().map(HashMap::len).sum::<usize>() >= Self::CAPACITY {
+            regexes.clear();
+        }
+        regexes
+            .entry(flags.to_string())
+            .or_default()
+            .insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+}
@@ -0,0 +1,141 @@
+use super::document::{is_truthy, sort_cmp};
+use super::scope::Scope;
+use super::{Evaluator, EvaluatorError};
+use crate::ast::statement::Statement;
+use crate::ast::{SortKey, Stage, AST};
+use serde_json::{Map, Value};
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+
+pub(super) type Stream<'a> = Box<dyn Iterator<Item = Result<Value, EvaluatorError>> + 'a>;
+
+impl Evaluator {
+    /// Stream the documents through a query and the stages of its pipeline.
+    ///
+    /// Documents are pulled one at a time, so `limit` stops reading the
+    /// source early. `sort` has to read every document that reaches it
+    /// before producing the first one.
+    pub fn pipeline<'a, I>(&'a self, ast: &'a AST, documents: I) -> Stream<'a>
+    where
+        I: IntoIterator<Item = Value>,
+        I::IntoIter: 'a,
+    {
+        let (source, stages) = match ast.statement() {
+            Statement::Pipeline { source, stages, .. } => (&**source, &stages[..]),
+            Statement::Select { span, .. } => {
+                return Box::new(std::iter::once(Err(EvaluatorError::StatementNotSupported(
+                    span.clone(),
+                ))))
+            }
+            statement => (statement, &[][..]),
+        };
+
+        let mut stream = self.source(source, documents);
+        for stage in stages {
+            stream = self.stage(stage, stream);
+        }
+
+        stream
+    }
+
+    /// The documents matching `source`.
+    pub(super) fn source<'a, I>(&'a self, source: &'a Statement, documents: I) -> Stream<'a>
+    where
+        I: IntoIterator<Item = Value>,
+        I::IntoIter: 'a,
+    {
+        Box::new(documents.into_iter().filter_map(move |document| {
+            match self.evaluate(source, &document, &Scope::default()) {
+                Ok(value) if is_truthy(&value) => Some(Ok(document)),
+                Ok(_) => None,
+                Err(error) => Some(Err(error)),
+            }
+        }))
+    }
+
+    pub(super) fn stage<'a>(&'a self, stage: &'a Stage, stream: Stream<'a>) -> Stream<'a> {
+        match stage {
+            Stage::Sort { keys, .. } => Box::new(std::iter::once(stream).flat_map(move |stream| {
+                match stream
+                    .collect::<Result<Vec<_>, _>>()
+                    .and_then(|documents| self.sort(keys, documents))
+                {
+                    Ok(documents) => documents.into_iter().map(Ok).collect(),
+                    Err(error) => vec![Err(error)],
+                }
+            })),
+            Stage::Group {
+                keys, aggregates, ..
+            } => Box::new(std::iter::once(stream).flat_map(move |stream| {
+                let fields: Vec<_> = keys.iter().chain(aggregates).cloned().collect();
+                let keys: Vec<_> = keys.iter().map(|(_, key)| key.clone()).collect();
+                match stream
+                    .collect::<Result<Vec<_>, _>>()
+                    .and_then(|documents| self.group(&keys, &fields, documents))
+                {
+                    Ok(rows) => rows.into_iter().map(Ok).collect(),
+                    Err(error) => vec![Err(error)],
+                }
+            })),
+            Stage::Limit { count, .. } => Box::new(stream.take(*count)),
+            Stage::Offset { count, .. } => Box::new(stream.skip(*count)),
+            Stage::Pick { fields, .. } => Box::new(stream.map(move |document| {
+                document.and_then(|document| self.project(fields, &document))
+            })),
+        }
+    }
+
+    /// Values of the keys for `document`.
+    pub(super) fn sort_values(
+        &self,
+        keys: &[SortKey],
+        document: &Value,
+    ) -> Result<Vec<Value>, EvaluatorError> {
+        let scope = Scope::default();
+        let mut values = vec![];
+        for key in keys {
+            values.push(self.evaluate(&key.expression, document, &scope)?);
+        }
+        Ok(values)
+    }
+
+    /// Stable sort of the documents by their keys, see [`sort_cmp`].
+    pub(super) fn sort<D: Borrow<Value>>(
+        &self,
+        keys: &[SortKey],
+        documents: Vec<D>,
+    ) -> Result<Vec<D>, EvaluatorError> {
+        let mut rows = vec![];
+        for document in documents {
+            rows.push((self.sort_values(keys, document.borrow())?, document));
+        }
+
+        rows.sort_by(|(left, _), (right, _)| sort_values_cmp(keys, left, right));
+
+        Ok(rows.into_iter().map(|(_, document)| document).collect())
+    }
+
+    /// Object with a property for each of the named expressions.
+    pub(super) fn project(
+        &self,
+        fields: &[(String, Statement)],
+        document: &Value,
+    ) -> Result<Value, EvaluatorError> {
+        let scope = Scope::default();
+        let mut object = Map::new();
+        for (name, expression) in fields {
+            object.insert(name.clone(), self.evaluate(expression, document, &scope)?);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// Order of the values of several sort keys, the first key that isn't a tie
+/// decides.
+pub(super) fn sort_values_cmp(keys: &[SortKey], left: &[Value], right: &[Value]) -> Ordering {
+    keys.iter()
+        .zip(left.iter().zip(right.iter()))
+        .map(|(key, (left, right))| sort_cmp(left, right, key))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
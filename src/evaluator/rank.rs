@@ -0,0 +1,266 @@
+use super::document::{edit_distance, glob_matches, is_truthy, lookup, texts};
+use super::scope::Scope;
+use super::{strip_root, Evaluator, EvaluatorError, RankOptions};
+use crate::analyzer::Analyzer;
+use crate::ast::statement::Statement;
+use crate::ast::value::Value as AstValue;
+use crate::ast::AST;
+use serde_json::Value;
+
+/// A term of the query, analyzed for each field it is scored on.
+struct Term {
+    /// The field of `title:fox`, free text is scored on every field.
+    field: Option<Vec<String>>,
+    query: Query,
+    boost: f64,
+}
+
+enum Query {
+    /// Words of free text, a phrase or a value, each one a term.
+    Text(String),
+    Wildcard(String),
+    Fuzzy(String, usize),
+}
+
+/// A term of the query analyzed for a field.
+enum Matcher {
+    Term(String),
+    Wildcard(String),
+    Fuzzy(String, usize),
+}
+
+impl Matcher {
+    /// Test the term and the normalized word of a token.
+    fn matches(&self, term: Option<&String>, word: &str) -> bool {
+        match self {
+            Matcher::Term(other) => term == Some(other),
+            Matcher::Wildcard(pattern) => glob_matches(pattern, word),
+            Matcher::Fuzzy(other, distance) => edit_distance(other, word, *distance).is_some(),
+        }
+    }
+}
+
+impl Evaluator {
+    /// Matching documents with their BM25 score, highest first and ties in
+    /// the order of `documents`.
+    ///
+    /// The terms are the words of the free text, wildcards, fuzzy terms and
+    /// phrases, each phrase scored as its words, and the values of
+    /// conditions like `title:fox` on a scored field. Terms under a negation
+    /// or a quantifier aren't scored. Each field is scored on its own, with
+    /// its analyzer and the frequency of the terms over all `documents`, and
+    /// the scores of the fields add up. `quick^2` multiplies the score of its
+    /// terms. The stages of a pipeline are ignored.
+    pub fn rank<I>(
+        &self,
+        ast: &AST,
+        documents: I,
+        options: &RankOptions,
+    ) -> Result<Vec<(f64, Value)>, EvaluatorError>
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        let statement = match ast.statement() {
+            Statement::Pipeline { source, .. } => source,
+            statement => statement,
+        };
+        let fields = match options.fields.is_empty() {
+            true => &self.options.default_fields,
+            false => &options.fields,
+        };
+        let mut terms = vec![];
+        collect_terms(statement, 1.0, &mut terms);
+        // Terms of each field and their boost.
+        let matchers: Vec<Vec<(Matcher, f64)>> = fields
+            .iter()
+            .map(|field| {
+                let path: Vec<String> = field.split('.').map(|s| s.to_string()).collect();
+                let analyzer = self.options.analyzer(field);
+                terms
+                    .iter()
+                    .filter(|term| term.field.as_ref().is_none_or(|other| *other == path))
+                    .flat_map(|term| {
+                        matchers(&term.query, analyzer)
+                            .into_iter()
+                            .map(|matcher| (matcher, term.boost))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let documents: Vec<Value> = documents.into_iter().collect();
+        // Frequency of each term in each field of each document, and the
+        // number of terms of the field.
+        let frequencies: Vec<Vec<(Vec<usize>, usize)>> = documents
+            .iter()
+            .map(|document| {
+                fields
+                    .iter()
+                    .zip(&matchers)
+                    .map(|(field, matchers)| {
+                        let path: Vec<String> = field.split('.').map(|s| s.to_string()).collect();
+                        frequencies(
+                            &lookup(document, &path),
+                            self.options.analyzer(field),
+                            matchers,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let count = documents.len() as f64;
+        let mut average_lengths = vec![0.0; fields.len()];
+        let mut document_frequencies: Vec<Vec<usize>> = matchers
+            .iter()
+            .map(|matchers| vec![0; matchers.len()])
+            .collect();
+        for document in &frequencies {
+            for (field, (frequencies, length)) in document.iter().enumerate() {
+                average_lengths[field] += *length as f64 / count;
+                for (term, frequency) in frequencies.iter().enumerate() {
+                    if *frequency > 0 {
+                        document_frequencies[field][term] += 1;
+                    }
+                }
+            }
+        }
+
+        let scope = Scope::default();
+        let mut ranked = vec![];
+        for (document, frequencies) in documents.into_iter().zip(frequencies) {
+            if !is_truthy(&self.evaluate(statement, &document, &scope)?) {
+                continue;
+            }
+            let mut score: f64 = 0.0;
+            for (field, (frequencies, length)) in frequencies.iter().enumerate() {
+                // Every field is empty when the average is 0.
+                let relative_length = match average_lengths[field] {
+                    0.0 => 0.0,
+                    average => *length as f64 / average,
+                };
+                for (term, frequency) in frequencies.iter().enumerate() {
+                    if *frequency == 0 {
+                        continue;
+                    }
+                    let frequency = *frequency as f64;
+                    let document_frequency = document_frequencies[field][term] as f64;
+                    let idf = (1.0
+                        + (count - document_frequency + 0.5) / (document_frequency + 0.5))
+                        .ln();
+                    score += matchers[field][term].1 * idf * frequency * (options.k1 + 1.0)
+                        / (frequency
+                            + options.k1 * (1.0 - options.b + options.b * relative_length));
+                }
+            }
+            ranked.push((score, document));
+        }
+        ranked.sort_by(|(left, _), (right, _)| right.total_cmp(left));
+        Ok(ranked)
+    }
+}
+
+/// Frequency of each term in the value of a field, and its number of
+/// terms.
+fn frequencies(
+    value: &Value,
+    analyzer: &dyn Analyzer,
+    matchers: &[(Matcher, f64)],
+) -> (Vec<usize>, usize) {
+    let mut frequencies = vec![0; matchers.len()];
+    let mut length = 0;
+    for text in texts(value) {
+        for token in analyzer.tokenize(&text) {
+            let term = analyzer.filter(&token);
+            let word = analyzer.normalize(&token);
+            if term.is_some() {
+                length += 1;
+            }
+            for (frequency, (matcher, _)) in frequencies.iter_mut().zip(matchers) {
+                if matcher.matches(term.as_ref(), &word) {
+                    *frequency += 1;
+                }
+            }
+        }
+    }
+    (frequencies, length)
+}
+
+fn collect_terms(statement: &Statement, boost: f64, terms: &mut Vec<Term>) {
+    match statement {
+        Statement::Expression { expresssion, .. } => {
+            for statement in expresssion {
+                collect_terms(statement, boost, terms);
+            }
+        }
+        Statement::Boost {
+            argument,
+            boost: factor,
+            ..
+        } => collect_terms(argument, boost * factor, terms),
+        Statement::BinaryExpression {
+            left,
+            operator,
+            right,
+            ..
+        } => match operator.as_str() {
+            "&&" | "||" => {
+                collect_terms(left, boost, terms);
+                collect_terms(right, boost, terms);
+            }
+            "=" | ":" => {
+                if let (Some(path), Some(query)) = (left.to_path().map(strip_root), query(right)) {
+                    terms.push(Term {
+                        field: Some(path),
+                        query,
+                        boost,
+                    });
+                }
+            }
+            _ => {}
+        },
+        statement => {
+            if let Some(query) = query(statement) {
+                terms.push(Term {
+                    field: None,
+                    query,
+                    boost,
+                });
+            }
+        }
+    }
+}
+
+fn query(statement: &Statement) -> Option<Query> {
+    match statement {
+        Statement::Text {
+            value: AstValue::String(text),
+            ..
+        }
+        | Statement::Literal {
+            value: AstValue::String(text),
+            ..
+        }
+        | Statement::Phrase { phrase: text, .. } => Some(Query::Text(text.clone())),
+        Statement::Text { value, .. } | Statement::Literal { value, .. } => {
+            Some(Query::Text(Value::from(value).to_string()))
+        }
+        Statement::Wildcard { pattern, .. } => Some(Query::Wildcard(pattern.clone())),
+        Statement::Fuzzy { term, distance, .. } => Some(Query::Fuzzy(term.clone(), *distance)),
+        _ => None,
+    }
+}
+
+fn matchers(query: &Query, analyzer: &dyn Analyzer) -> Vec<Matcher> {
+    match query {
+        Query::Text(text) => analyzer
+            .analyze(text)
+            .into_iter()
+            .map(Matcher::Term)
+            .collect(),
+        Query::Wildcard(pattern) => vec![Matcher::Wildcard(analyzer.normalize(pattern))],
+        Query::Fuzzy(term, distance) => {
+            vec![Matcher::Fuzzy(analyzer.normalize(term), *distance)]
+        }
+    }
+}
@@ -0,0 +1,36 @@
+#[derive(Debug, Clone)]
+pub struct RankOptions {
+    /// Text fields scored, paths like `title` or `specs.description`. The
+    /// default fields of the evaluator when empty.
+    pub fields: Vec<String>,
+    /// Saturation of the frequency of a term, `1.2` by default.
+    pub k1: f64,
+    /// Normalization by the length of the field, from `0` to `1`, `0.75` by
+    /// default.
+    pub b: f64,
+}
+
+impl RankOptions {
+    pub fn set_fields<A: ToString>(&mut self, fields: Vec<A>) -> &mut Self {
+        self.fields = fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+    pub fn set_k1(&mut self, k1: f64) -> &mut Self {
+        self.k1 = k1;
+        self
+    }
+    pub fn set_b(&mut self, b: f64) -> &mut Self {
+        self.b = b;
+        self
+    }
+}
+
+impl Default for RankOptions {
+    fn default() -> Self {
+        Self {
+            fields: vec![],
+            k1: 1.2,
+            b: 0.75,
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use serde_json::Value;
+
+/// Paths rebound while a quantifier walks the elements of a collection.
+///
+/// A binding replaces a path prefix by an element, so with `variants` bound
+/// the path `variants.stock` resolves to `stock` of the current variant.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Scope {
+    bindings: Vec<(Vec<String>, Value)>,
+}
+
+impl Scope {
+    pub(crate) fn with(&self, prefix: Vec<String>, value: Value) -> Self {
+        let mut scope = self.clone();
+        scope.bindings.push((prefix, value));
+        scope
+    }
+
+    /// Return the innermost binding that prefixes `path` and the rest of the path.
+    pub(crate) fn find<'a, 'b>(&'a self, path: &'b [String]) -> Option<(&'a Value, &'b [String])> {
+        self.bindings
+            .iter()
+            .rev()
+            .find(|(prefix, _)| path.starts_with(prefix))
+            .map(|(prefix, value)| (value, &path[prefix.len()..]))
+    }
+}
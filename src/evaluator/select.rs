@@ -0,0 +1,93 @@
+use super::document::is_truthy;
+use super::scope::Scope;
+use super::{Evaluator, EvaluatorError};
+use crate::ast::statement::Statement;
+use crate::ast::AST;
+use serde_json::Value;
+use std::collections::HashMap;
+
+impl Evaluator {
+    /// Run a `SELECT` over the documents of the named collections.
+    ///
+    /// Matches of the `WHERE` clause are sorted by the `ORDER BY` keys with a
+    /// stable sort, then `OFFSET` documents are skipped and at most `LIMIT`
+    /// are projected. Grouped, the rows of the groups are sorted instead.
+    pub fn select(
+        &self,
+        ast: &AST,
+        collections: &HashMap<String, Vec<Value>>,
+    ) -> Result<Vec<Value>, EvaluatorError> {
+        let (span, projection, from, r#where, group_by, order_by, limit, offset) =
+            match ast.statement() {
+                Statement::Select {
+                    span,
+                    projection,
+                    from,
+                    r#where,
+                    group_by,
+                    order_by,
+                    limit,
+                    offset,
+                } => (
+                    span, projection, from, r#where, group_by, order_by, limit, offset,
+                ),
+                statement => {
+                    return Err(EvaluatorError::StatementNotSupported(
+                        statement.clone().to_span(),
+                    ))
+                }
+            };
+        let scope = Scope::default();
+
+        let documents = collections
+            .get(from)
+            .ok_or_else(|| EvaluatorError::CollectionNotFound(from.clone(), span.clone()))?;
+
+        let mut matches = vec![];
+        for document in documents {
+            if let Some(r#where) = r#where {
+                if !is_truthy(&self.evaluate(r#where, document, &scope)?) {
+                    continue;
+                }
+            }
+            matches.push(document);
+        }
+
+        let grouped = !group_by.is_empty()
+            || projection
+                .iter()
+                .flatten()
+                .any(|(_, field)| matches!(field, Statement::Aggregate { .. }));
+        if grouped {
+            let fields = match projection {
+                Some(projection) => projection.clone(),
+                None => group_by
+                    .iter()
+                    .filter_map(|key| Some((key.default_name()?, key.clone())))
+                    .collect(),
+            };
+            let rows = self.group(group_by, &fields, matches)?;
+            return Ok(self
+                .sort(order_by, rows)?
+                .into_iter()
+                .skip(offset.unwrap_or(0))
+                .take(limit.unwrap_or(usize::MAX))
+                .collect());
+        }
+
+        let mut results = vec![];
+        for document in self
+            .sort(order_by, matches)?
+            .into_iter()
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(usize::MAX))
+        {
+            results.push(match projection {
+                None => document.clone(),
+                Some(projection) => self.project(projection, document)?,
+            });
+        }
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,39 @@
+#[derive(Debug, Clone)]
+pub struct SnippetOptions {
+    /// Written before each highlighted range, `<em>` by default.
+    pub pre_tag: String,
+    /// Written after each highlighted range, `</em>` by default.
+    pub post_tag: String,
+    /// Characters of the text kept around the first range, `100` by default
+    /// and the whole text when `0`. Markers and ellipses aren't counted.
+    pub max_length: usize,
+    /// Written where the text is cut, `…` by default.
+    pub ellipsis: String,
+}
+
+impl SnippetOptions {
+    pub fn set_tags<A: ToString>(&mut self, pre_tag: A, post_tag: A) -> &mut Self {
+        self.pre_tag = pre_tag.to_string();
+        self.post_tag = post_tag.to_string();
+        self
+    }
+    pub fn set_max_length(&mut self, max_length: usize) -> &mut Self {
+        self.max_length = max_length;
+        self
+    }
+    pub fn set_ellipsis<A: ToString>(&mut self, ellipsis: A) -> &mut Self {
+        self.ellipsis = ellipsis.to_string();
+        self
+    }
+}
+
+impl Default for SnippetOptions {
+    fn default() -> Self {
+        Self {
+            pre_tag: "<em>".to_string(),
+            post_tag: "</em>".to_string(),
+            max_length: 100,
+            ellipsis: "…".to_string(),
+        }
+    }
+}
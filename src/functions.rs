@@ -0,0 +1,33 @@
+//! Functions callable from a query, like `lower(name) = "x"`.
+//!
+//! A [`FunctionRegistry`] maps names to a [`Function`]: its signature, the
+//! implementation used by the evaluator and the templates used by code
+//! generators. [`FunctionRegistry::default`] has the built-in functions and
+//! more can be registered:
+//!
+//! ```
+//! use ql::functions::{Function, FunctionRegistry, FunctionSignature, ValueType};
+//! use serde_json::Value;
+//!
+//! let mut functions = FunctionRegistry::default();
+//! functions.register(
+//!     "double",
+//!     Function::new(
+//!         FunctionSignature::new(vec![ValueType::Number], ValueType::Number),
+//!         |arguments| (arguments[0].as_f64().unwrap() * 2.0).into(),
+//!     )
+//!     .with_translation("typescript", "({0} * 2)"),
+//! );
+//! assert!(functions.get("double").is_some());
+//! ```
+
+mod builtins;
+mod function;
+mod function_registry;
+mod function_signature;
+mod value_type;
+
+pub use self::function::Function;
+pub use self::function_registry::FunctionRegistry;
+pub use self::function_signature::FunctionSignature;
+pub use self::value_type::ValueType;
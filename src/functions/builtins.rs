@@ -0,0 +1,83 @@
+use super::function::Function;
+use super::function_registry::FunctionRegistry;
+use super::function_signature::FunctionSignature;
+use super::value_type::ValueType;
+use serde_json::Value;
+
+pub(crate) fn register(registry: &mut FunctionRegistry) {
+    registry
+        .register(
+            "lower",
+            Function::new(
+                FunctionSignature::new(vec![ValueType::String], ValueType::String),
+                |arguments| string(&arguments[0]).to_lowercase().into(),
+            )
+            .with_translation("typescript", "{0}.toLowerCase()"),
+        )
+        .register(
+            "upper",
+            Function::new(
+                FunctionSignature::new(vec![ValueType::String], ValueType::String),
+                |arguments| string(&arguments[0]).to_uppercase().into(),
+            )
+            .with_translation("typescript", "{0}.toUpperCase()"),
+        )
+        .register(
+            "len",
+            Function::new(
+                FunctionSignature::new(vec![ValueType::Any], ValueType::Number),
+                |arguments| match &arguments[0] {
+                    Value::String(s) => s.chars().count().into(),
+                    Value::Array(items) => items.len().into(),
+                    Value::Object(map) => map.len().into(),
+                    _ => Value::Null,
+                },
+            )
+            .with_translation("typescript", "{0}.length"),
+        )
+        .register(
+            "starts_with",
+            Function::new(
+                FunctionSignature::new(
+                    vec![ValueType::String, ValueType::String],
+                    ValueType::Boolean,
+                ),
+                |arguments| {
+                    string(&arguments[0])
+                        .starts_with(string(&arguments[1]))
+                        .into()
+                },
+            )
+            .with_translation("typescript", "{0}.startsWith({1})"),
+        )
+        .register(
+            "ends_with",
+            Function::new(
+                FunctionSignature::new(
+                    vec![ValueType::String, ValueType::String],
+                    ValueType::Boolean,
+                ),
+                |arguments| {
+                    string(&arguments[0])
+                        .ends_with(string(&arguments[1]))
+                        .into()
+                },
+            )
+            .with_translation("typescript", "{0}.endsWith({1})"),
+        )
+        .register(
+            "contains",
+            Function::new(
+                FunctionSignature::new(
+                    vec![ValueType::String, ValueType::String],
+                    ValueType::Boolean,
+                ),
+                |arguments| string(&arguments[0]).contains(string(&arguments[1])).into(),
+            )
+            .with_translation("typescript", "{0}.includes({1})"),
+        );
+}
+
+fn string(value: &Value) -> &str {
+    value.as_str().unwrap_or_default()
+}
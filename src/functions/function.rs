@@ -0,0 +1,90 @@
+use super::function_signature::FunctionSignature;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+type Implementation = Arc<dyn Fn(&[Value]) -> Value + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Function {
+    pub signature: FunctionSignature,
+    implementation: Implementation,
+    /// Template of the call for each code generator target, `{0}` being
+    /// replaced by the first argument, `{1}` by the second and so on.
+    translations: HashMap<String, String>,
+}
+
+impl Function {
+    pub fn new<F>(signature: FunctionSignature, implementation: F) -> Self
+    where
+        F: Fn(&[Value]) -> Value + Send + Sync + 'static,
+    {
+        Self {
+            signature,
+            implementation: Arc::new(implementation),
+            translations: HashMap::new(),
+        }
+    }
+
+    pub fn with_translation<A: ToString, B: ToString>(mut self, target: A, template: B) -> Self {
+        self.translations
+            .insert(target.to_string(), template.to_string());
+        self
+    }
+
+    /// Call the function, arguments not matching the signature make the
+    /// result `null`, so `lower(name) = "x"` doesn't match documents
+    /// without `name`.
+    pub fn call(&self, arguments: &[Value]) -> Value {
+        let valid = arguments.len() == self.signature.arity()
+            && self
+                .signature
+                .arguments
+                .iter()
+                .zip(arguments)
+                .all(|(value_type, argument)| value_type.matches(argument));
+        if !valid {
+            return Value::Null;
+        }
+        (self.implementation)(arguments)
+    }
+
+    /// Code of the call for a target, each `{i}` of the template replaced
+    /// by the code of the argument `i` in a single pass, so placeholders
+    /// within the code of an argument are kept. Other braces are kept as
+    /// written.
+    pub fn translate<A: AsRef<str>>(&self, target: &str, arguments: &[A]) -> Option<String> {
+        let mut template = self.translations.get(target)?.as_str();
+        let mut code = String::new();
+        while let Some(start) = template.find('{') {
+            code.push_str(&template[..start]);
+            template = &template[start..];
+            let argument = template[1..]
+                .find('}')
+                .and_then(|end| Some((end, template[1..end + 1].parse::<usize>().ok()?)))
+                .and_then(|(end, index)| Some((end, arguments.get(index)?)));
+            match argument {
+                Some((end, argument)) => {
+                    code.push_str(argument.as_ref());
+                    template = &template[end + 2..];
+                }
+                None => {
+                    code.push('{');
+                    template = &template[1..];
+                }
+            }
+        }
+        code.push_str(template);
+        Some(code)
+    }
+}
+
+impl fmt::Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Function")
+            .field("signature", &self.signature)
+            .field("translations", &self.translations)
+            .finish()
+    }
+}
@@ -0,0 +1,48 @@
+use super::builtins;
+use super::function::Function;
+use crate::ast::AGGREGATES;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct FunctionRegistry {
+    functions: HashMap<String, Function>,
+}
+
+impl FunctionRegistry {
+    /// Creates a registry without functions, see [`FunctionRegistry::default`]
+    /// for one with the built-in functions.
+    pub fn new() -> Self {
+        Self {
+            functions: HashMap::new(),
+        }
+    }
+
+    /// Register a function, replacing any with the same name.
+    ///
+    /// # Panics
+    ///
+    /// Panics with the name of an aggregate like `count` or `max`, which
+    /// are parsed as aggregates and would never call the function.
+    pub fn register<A: ToString>(&mut self, name: A, function: Function) -> &mut Self {
+        let name = name.to_string();
+        assert!(
+            !AGGREGATES.contains(&name.as_str()),
+            "`{}` is an aggregate and can't be registered",
+            name
+        );
+        self.functions.insert(name, function);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+}
+
+impl Default for FunctionRegistry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        builtins::register(&mut registry);
+        registry
+    }
+}
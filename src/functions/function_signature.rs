@@ -0,0 +1,18 @@
+use super::value_type::ValueType;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSignature {
+    /// Type of each argument, its length is the arity of the function.
+    pub arguments: Vec<ValueType>,
+    pub returns: ValueType,
+}
+
+impl FunctionSignature {
+    pub fn new(arguments: Vec<ValueType>, returns: ValueType) -> Self {
+        Self { arguments, returns }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.arguments.len()
+    }
+}
@@ -0,0 +1,128 @@
+//! In-memory inverted index answering queries without scanning every
+//! document.
+//!
+//! Each word of a text field lists the documents having it, the words are
+//! those of the analyzer of the field and each analyzed term lists the words
+//! it comes from, see [`crate::analyzer`]. The numbers
+//! of a numeric field are kept in order. A query is planned into the
+//! documents that may match, intersecting the candidates of the conditions
+//! of a conjunction and joining those of a disjunction, and each candidate
+//! is then checked by the [`Evaluator`], so the results are always those of
+//! [`Evaluator::matches`]. A condition the index can't answer, like a
+//! negation or a field without index, leaves the candidates of the others,
+//! and a query without any condition answered by the index scans every
+//! document.
+//!
+//! ```text
+//! laptop price < 500     documents with the word `laptop` and a price below 500
+//! laptop OR -used        every document, `-used` can't be answered
+//! ```
+
+mod index_options;
+mod number;
+mod planner;
+
+pub use self::index_options::IndexOptions;
+
+use self::number::Number;
+use crate::ast::AST;
+use crate::evaluator::{elements, lookup, texts, Evaluator, EvaluatorError};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+pub struct Index {
+    options: IndexOptions,
+    evaluator: Evaluator,
+    documents: Vec<Value>,
+    /// Ids of the documents having each normalized word, in order, for each
+    /// text field, see [`crate::analyzer`].
+    postings: Vec<HashMap<String, Vec<usize>>>,
+    /// Normalized words of each analyzed term, for each text field.
+    terms: Vec<HashMap<String, Vec<String>>>,
+    /// Ids of the documents having each number, in order of the number, for
+    /// each numeric field.
+    numbers: Vec<BTreeMap<Number, Vec<usize>>>,
+}
+
+impl Index {
+    pub fn new(options: &IndexOptions) -> Self {
+        Self {
+            options: options.clone(),
+            evaluator: Evaluator::new(&options.evaluator),
+            documents: vec![],
+            postings: vec![HashMap::new(); options.text_fields.len()],
+            terms: vec![HashMap::new(); options.text_fields.len()],
+            numbers: vec![BTreeMap::new(); options.numeric_fields.len()],
+        }
+    }
+
+    /// Add a document, its id is the number of documents added before.
+    pub fn insert(&mut self, document: Value) -> usize {
+        let id = self.documents.len();
+        for (position, field) in self.options.text_fields.iter().enumerate() {
+            let analyzer = self.options.evaluator.analyzer(field);
+            let mut words: Vec<String> = texts(&lookup(&document, &path(field)))
+                .iter()
+                .flat_map(|text| analyzer.words(text))
+                .collect();
+            words.sort();
+            words.dedup();
+            for word in words {
+                let ids = self.postings[position].entry(word.clone()).or_default();
+                if ids.is_empty() {
+                    if let Some(term) = analyzer.filter(&word) {
+                        self.terms[position].entry(term).or_default().push(word);
+                    }
+                }
+                ids.push(id);
+            }
+        }
+        for (field, numbers) in self.options.numeric_fields.iter().zip(&mut self.numbers) {
+            for value in elements(lookup(&document, &path(field))) {
+                if let Some(number) = value.as_f64() {
+                    numbers.entry(Number::new(number)).or_default().push(id);
+                }
+            }
+        }
+        self.documents.push(document);
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Value> {
+        self.documents.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Documents matching the query in the order they were added, the
+    /// stages of a pipeline are ignored.
+    pub fn search(&self, ast: &AST) -> Result<Vec<&Value>, EvaluatorError> {
+        let ids = self
+            .candidates(ast)
+            .unwrap_or_else(|| (0..self.documents.len()).collect());
+        let mut matches = vec![];
+        for id in ids {
+            let document = &self.documents[id];
+            if self.evaluator.test(ast, document)? {
+                matches.push(document);
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Ids of the documents the planner checks for the query, in order, or
+    /// `None` when it scans every document.
+    pub fn candidates(&self, ast: &AST) -> Option<Vec<usize>> {
+        self.plan(ast.statement())
+    }
+}
+
+fn path(field: &str) -> Vec<String> {
+    field.split('.').map(|s| s.to_string()).collect()
+}
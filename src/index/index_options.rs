@@ -0,0 +1,27 @@
+use crate::evaluator::EvaluatorOptions;
+
+#[derive(Debug, Clone, Default)]
+pub struct IndexOptions {
+    /// Fields whose words are indexed, paths like `title` or `specs.color`.
+    pub text_fields: Vec<String>,
+    /// Fields whose numbers are indexed in order, paths like `price`.
+    pub numeric_fields: Vec<String>,
+    /// Options of the evaluator checking the documents, its default fields
+    /// are the fields of the free text.
+    pub evaluator: EvaluatorOptions,
+}
+
+impl IndexOptions {
+    pub fn set_text_fields<A: ToString>(&mut self, text_fields: Vec<A>) -> &mut Self {
+        self.text_fields = text_fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+    pub fn set_numeric_fields<A: ToString>(&mut self, numeric_fields: Vec<A>) -> &mut Self {
+        self.numeric_fields = numeric_fields.iter().map(|f| f.to_string()).collect();
+        self
+    }
+    pub fn set_evaluator(&mut self, evaluator: EvaluatorOptions) -> &mut Self {
+        self.evaluator = evaluator;
+        self
+    }
+}
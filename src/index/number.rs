@@ -0,0 +1,32 @@
+use std::cmp::Ordering;
+
+/// Number of a numeric field, totally ordered to key the numbers in order.
+/// `-0.0` is `0.0`, the JSON numbers are never `NaN`.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Number(f64);
+
+impl Number {
+    pub(super) fn new(number: f64) -> Self {
+        Self(number + 0.0)
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Number {}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Number {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
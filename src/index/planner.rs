@@ -0,0 +1,269 @@
+use super::{path, Index, Number};
+use crate::analyzer::Analyzer;
+use crate::ast::statement::Statement;
+use crate::ast::value::Value as AstValue;
+use crate::evaluator::{edit_distance, glob_matches, strip_root};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+impl Index {
+    /// Sorted ids of the documents that may match `statement`, `None` when
+    /// any document may.
+    pub(super) fn plan(&self, statement: &Statement) -> Option<Vec<usize>> {
+        match statement {
+            Statement::Pipeline { source, .. } => self.plan(source),
+            Statement::Expression { expresssion, .. } => expresssion
+                .iter()
+                .map(|statement| self.plan(statement))
+                .fold(None, intersection),
+            Statement::Boost { argument, .. } => self.plan(argument),
+            Statement::Text { value, .. } => {
+                let text = match value {
+                    AstValue::String(text) => text.clone(),
+                    value => Value::from(value).to_string(),
+                };
+                // Each word of the text is a word of the field, only the
+                // first may be cut at its start and the last at its end, so
+                // only those scan the vocabulary. Text without words, like
+                // `-`, is contained in strings without any word of the
+                // field and scans every document.
+                self.free_text(|field, analyzer| {
+                    let words = analyzer.words(&text);
+                    let (first, last) = match words.as_slice() {
+                        [] => return None,
+                        [word] => {
+                            return self.vocabulary(field, |other| other.contains(word.as_str()))
+                        }
+                        [first, .., last] => (first, last),
+                    };
+                    let ids = words[1..words.len() - 1]
+                        .iter()
+                        .map(|word| Some(self.word(field, word)))
+                        .fold(None, intersection);
+                    if ids.as_ref().is_some_and(|ids| ids.is_empty()) {
+                        return ids;
+                    }
+                    let ids = intersection(
+                        ids,
+                        self.vocabulary(field, |other| other.ends_with(first.as_str())),
+                    );
+                    intersection(
+                        ids,
+                        self.vocabulary(field, |other| other.starts_with(last.as_str())),
+                    )
+                })
+            }
+            Statement::Wildcard { pattern, .. } => self.free_text(|field, analyzer| {
+                let pattern = analyzer.normalize(pattern);
+                self.vocabulary(field, |word| glob_matches(&pattern, word))
+            }),
+            Statement::Fuzzy { term, distance, .. } => self.free_text(|field, analyzer| {
+                let term = analyzer.normalize(term);
+                self.vocabulary(field, |word| {
+                    edit_distance(&term, word, *distance).is_some()
+                })
+            }),
+            Statement::Phrase { phrase, .. } => {
+                self.free_text(|field, analyzer| self.all_terms(field, &analyzer.analyze(phrase)))
+            }
+            Statement::BinaryExpression {
+                left,
+                operator,
+                right,
+                ..
+            } => match operator.as_str() {
+                "&&" => intersection(self.plan(left), self.plan(right)),
+                "||" => union(self.plan(left), self.plan(right)),
+                operator => self.condition(&left.to_path().map(strip_root)?, operator, right),
+            },
+            _ => None,
+        }
+    }
+
+    /// Candidates of a condition on a field, like `brand:acme` or
+    /// `price:10..20`.
+    fn condition(&self, field: &[String], operator: &str, value: &Statement) -> Option<Vec<usize>> {
+        let text_field = self
+            .options
+            .text_fields
+            .iter()
+            .position(|f| path(f) == field);
+        let numeric_field = self
+            .options
+            .numeric_fields
+            .iter()
+            .position(|f| path(f) == field);
+        match (operator, value) {
+            (
+                "=" | ":",
+                Statement::Literal {
+                    value: AstValue::String(text),
+                    ..
+                }
+                | Statement::Phrase { phrase: text, .. },
+            ) => {
+                let field = text_field?;
+                let analyzer = self
+                    .options
+                    .evaluator
+                    .analyzer(&self.options.text_fields[field]);
+                self.all_terms(field, &analyzer.analyze(text))
+            }
+            (
+                "=" | ":",
+                Statement::RangeExpression {
+                    start,
+                    end,
+                    start_exclusive,
+                    end_exclusive,
+                    ..
+                },
+            ) => {
+                let start = match (start, start_exclusive) {
+                    (Some(start), false) => Bound::Included(number(start)?),
+                    (Some(start), true) => Bound::Excluded(number(start)?),
+                    (None, _) => Bound::Unbounded,
+                };
+                let end = match (end, end_exclusive) {
+                    (Some(end), false) => Bound::Included(number(end)?),
+                    (Some(end), true) => Bound::Excluded(number(end)?),
+                    (None, _) => Bound::Unbounded,
+                };
+                Some(self.range(numeric_field?, start, end))
+            }
+            (operator, value) => {
+                let number = number(value)?;
+                let (start, end) = match operator {
+                    "=" | ":" => (Bound::Included(number), Bound::Included(number)),
+                    ">" => (Bound::Excluded(number), Bound::Unbounded),
+                    ">=" => (Bound::Included(number), Bound::Unbounded),
+                    "<" => (Bound::Unbounded, Bound::Excluded(number)),
+                    "<=" => (Bound::Unbounded, Bound::Included(number)),
+                    _ => return None,
+                };
+                Some(self.range(numeric_field?, start, end))
+            }
+        }
+    }
+
+    /// Candidates of free text, those of each default field joined, when
+    /// every default field is a text field.
+    fn free_text<F>(&self, candidates: F) -> Option<Vec<usize>>
+    where
+        F: Fn(usize, &dyn Analyzer) -> Option<Vec<usize>>,
+    {
+        let mut ids = vec![];
+        for field in &self.options.evaluator.default_fields {
+            let position = self.options.text_fields.iter().position(|f| f == field)?;
+            ids.extend(candidates(
+                position,
+                self.options.evaluator.analyzer(field),
+            )?);
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        Some(ids)
+    }
+
+    /// Documents having a word of a text field accepted by `accept`.
+    fn vocabulary<F: Fn(&str) -> bool>(&self, field: usize, accept: F) -> Option<Vec<usize>> {
+        let mut ids: Vec<usize> = self.postings[field]
+            .iter()
+            .filter(|(word, _)| accept(word))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        Some(ids)
+    }
+
+    /// Documents having a normalized word of a text field.
+    fn word(&self, field: usize, word: &str) -> Vec<usize> {
+        self.postings[field].get(word).cloned().unwrap_or_default()
+    }
+
+    /// Documents having every analyzed term in a text field, `None` without
+    /// terms.
+    fn all_terms(&self, field: usize, terms: &[String]) -> Option<Vec<usize>> {
+        terms
+            .iter()
+            .map(|term| {
+                let mut ids: Vec<usize> = self.terms[field]
+                    .get(term)
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|word| self.postings[field][word].iter().copied())
+                    .collect();
+                ids.sort_unstable();
+                ids.dedup();
+                Some(ids)
+            })
+            .fold(None, intersection)
+    }
+
+    fn range(&self, field: usize, start: Bound<f64>, end: Bound<f64>) -> Vec<usize> {
+        let (start, end) = (start.map(Number::new), end.map(Number::new));
+        // `BTreeMap::range` panics on bounds out of order.
+        let empty = match (&start, &end) {
+            (Bound::Included(start), Bound::Included(end)) => start > end,
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) => start >= end,
+            _ => false,
+        };
+        if empty {
+            return vec![];
+        }
+        let mut ids: Vec<usize> = self.numbers[field]
+            .range((start, end))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+fn number(statement: &Statement) -> Option<f64> {
+    match statement {
+        Statement::Literal {
+            value: AstValue::Number(number),
+            ..
+        } => Some(*number),
+        _ => None,
+    }
+}
+
+/// Candidates of both conditions, `None` being every document.
+fn intersection(left: Option<Vec<usize>>, right: Option<Vec<usize>>) -> Option<Vec<usize>> {
+    match (left, right) {
+        (Some(left), Some(right)) => {
+            let (mut i, mut j, mut ids) = (0, 0, vec![]);
+            while i < left.len() && j < right.len() {
+                match left[i].cmp(&right[j]) {
+                    Ordering::Less => i += 1,
+                    Ordering::Greater => j += 1,
+                    Ordering::Equal => {
+                        ids.push(left[i]);
+                        i += 1;
+                        j += 1;
+                    }
+                }
+            }
+            Some(ids)
+        }
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+/// Candidates of either condition.
+fn union(left: Option<Vec<usize>>, right: Option<Vec<usize>>) -> Option<Vec<usize>> {
+    let mut ids = left?;
+    ids.extend(right?);
+    ids.sort_unstable();
+    ids.dedup();
+    Some(ids)
+}
@@ -1,4 +1,16 @@
+pub mod analyzer;
 pub mod ast;
+pub mod bytecode;
+pub mod codegen;
+pub mod evaluator;
+pub mod functions;
+pub mod index;
+pub mod percolator;
+pub mod queryable;
 pub mod tokenizer;
 
+pub use evaluator::Evaluator;
+pub use index::Index;
+pub use percolator::Percolator;
+pub use queryable::Queryable;
 pub use tokenizer::Tokenizer;
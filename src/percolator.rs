@@ -0,0 +1,247 @@
+//! Match documents against many stored queries.
+//!
+//! Each query is indexed by keys a document must have at least one of to
+//! match it: a field equal to a value for `brand:acme` or
+//! `status in ["open", "pending"]`, a field with a value for comparisons,
+//! ranges, wildcards, fuzzy terms, phrases and regexes, and any default
+//! field for free text. A conjunction is indexed by its most selective
+//! condition and a disjunction by the keys of both sides. A query without
+//! such keys, like a negation or a quantifier, is checked for every
+//! document. The candidates are then checked by the [`Evaluator`], so the
+//! results are always those of [`Evaluator::matches`].
+//!
+//! ```text
+//! brand:acme price < 500     documents with `brand` equal to `acme`
+//! laptop OR price > 10       documents with a default field or a price
+//! -used                      every document
+//! ```
+
+mod query_key;
+
+use self::query_key::{term, QueryKey};
+use crate::ast::statement::Statement;
+use crate::ast::AST;
+use crate::evaluator::{strip_root, Evaluator, EvaluatorError, EvaluatorOptions};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+pub struct Percolator {
+    options: EvaluatorOptions,
+    evaluator: Evaluator,
+    queries: Vec<AST>,
+    /// Ids of the queries indexed by each key, in order.
+    keys: HashMap<QueryKey, Vec<usize>>,
+    /// Ids of the queries without keys, checked for every document.
+    unindexed: Vec<usize>,
+}
+
+impl Percolator {
+    pub fn new(options: &EvaluatorOptions) -> Self {
+        Self {
+            options: options.clone(),
+            evaluator: Evaluator::new(options),
+            queries: vec![],
+            keys: HashMap::new(),
+            unindexed: vec![],
+        }
+    }
+
+    /// Add a query, its id is the number of queries added before.
+    pub fn insert(&mut self, ast: AST) -> usize {
+        let id = self.queries.len();
+        match self.requirements(ast.statement()) {
+            Some(keys) => {
+                for key in keys.into_iter().collect::<HashSet<_>>() {
+                    self.keys.entry(key).or_default().push(id);
+                }
+            }
+            None => self.unindexed.push(id),
+        }
+        self.queries.push(ast);
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<&AST> {
+        self.queries.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.queries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queries.is_empty()
+    }
+
+    /// Ids of the queries matching the document, in order, the stages of a
+    /// pipeline are ignored.
+    pub fn percolate(&self, document: &Value) -> Result<Vec<usize>, EvaluatorError> {
+        let mut matches = vec![];
+        for id in self.candidates(document) {
+            if self.evaluator.test(&self.queries[id], document)? {
+                matches.push(id);
+            }
+        }
+        Ok(matches)
+    }
+
+    /// Ids of the queries checked for the document, in order.
+    pub fn candidates(&self, document: &Value) -> Vec<usize> {
+        let mut keys = HashSet::new();
+        document_keys(document, String::new(), &mut keys);
+        let mut ids: Vec<usize> = keys
+            .iter()
+            .filter_map(|key| self.keys.get(key))
+            .flatten()
+            .chain(&self.unindexed)
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// Keys a document has at least one of when it matches `statement`,
+    /// `None` when any document may match.
+    fn requirements(&self, statement: &Statement) -> Option<Vec<QueryKey>> {
+        match statement {
+            Statement::Pipeline { source, .. } => self.requirements(source),
+            Statement::Expression { expresssion, .. } => expresssion
+                .iter()
+                .map(|statement| self.requirements(statement))
+                .fold(None, most_selective),
+            Statement::Boost { argument, .. } => self.requirements(argument),
+            // An empty phrase matches every value, `null` included.
+            Statement::Phrase { phrase, .. }
+                if self
+                    .options
+                    .default_fields
+                    .iter()
+                    .any(|field| self.options.analyzer(field).analyze(phrase).is_empty()) =>
+            {
+                None
+            }
+            Statement::Text { .. }
+            | Statement::Wildcard { .. }
+            | Statement::Fuzzy { .. }
+            | Statement::Phrase { .. } => Some(
+                self.options
+                    .default_fields
+                    .iter()
+                    .map(|field| QueryKey::Field(field.clone()))
+                    .collect(),
+            ),
+            Statement::BinaryExpression {
+                left,
+                operator,
+                right,
+                ..
+            } => match operator.as_str() {
+                "&&" => most_selective(self.requirements(left), self.requirements(right)),
+                "||" => {
+                    let mut keys = self.requirements(left)?;
+                    keys.extend(self.requirements(right)?);
+                    Some(keys)
+                }
+                operator => self.condition(&field(left)?, operator, right),
+            },
+            _ => None,
+        }
+    }
+
+    /// Keys of a condition on a field, like `brand:acme` or `price > 10`.
+    fn condition(&self, field: &str, operator: &str, value: &Statement) -> Option<Vec<QueryKey>> {
+        let exists = Some(vec![QueryKey::Field(field.to_string())]);
+        match (operator, value) {
+            ("=" | ":", Statement::Literal { value, .. }) => Some(vec![QueryKey::Term(
+                field.to_string(),
+                term(&Value::from(value))?,
+            )]),
+            ("=" | ":", Statement::Phrase { phrase, .. }) => {
+                match self.options.analyzer(field).analyze(phrase).is_empty() {
+                    true => None,
+                    false => exists,
+                }
+            }
+            ("=" | ":", Statement::RangeExpression { start, end, .. }) => {
+                match start.is_none() && end.is_none() {
+                    true => None,
+                    false => exists,
+                }
+            }
+            ("=" | ":", Statement::Wildcard { .. } | Statement::Fuzzy { .. }) => exists,
+            (">" | "<" | ">=" | "<=", Statement::Literal { .. }) => exists,
+            ("in", Statement::ArrayExpression { elements, .. }) => elements
+                .iter()
+                .map(|element| match element {
+                    Statement::Literal { value, .. } => Some(QueryKey::Term(
+                        field.to_string(),
+                        term(&Value::from(value))?,
+                    )),
+                    _ => None,
+                })
+                .collect(),
+            ("=~", _) => exists,
+            _ => None,
+        }
+    }
+}
+
+/// Path of the field of a condition, `None` when it selects an element of
+/// an array, which the keys of a document don't tell apart.
+fn field(statement: &Statement) -> Option<String> {
+    let path = strip_root(statement.to_path()?);
+    if path.is_empty() || path.iter().any(|segment| segment.parse::<usize>().is_ok()) {
+        return None;
+    }
+    Some(path.join("."))
+}
+
+/// The keys of a conjunction with the fewest candidates, field values
+/// before fields.
+fn most_selective(
+    left: Option<Vec<QueryKey>>,
+    right: Option<Vec<QueryKey>>,
+) -> Option<Vec<QueryKey>> {
+    let cost = |keys: &Vec<QueryKey>| {
+        let fields = keys.iter().any(|key| matches!(key, QueryKey::Field(_)));
+        (fields, keys.len())
+    };
+    match (left, right) {
+        (Some(left), Some(right)) if cost(&right) < cost(&left) => Some(right),
+        (Some(left), _) => Some(left),
+        (None, right) => right,
+    }
+}
+
+/// Keys of a value at `path`, the elements of an array having the path of
+/// the array like [`crate::evaluator`] projects them.
+fn document_keys(value: &Value, path: String, keys: &mut HashSet<QueryKey>) {
+    if value.is_null() {
+        return;
+    }
+    if !path.is_empty() {
+        keys.insert(QueryKey::Field(path.clone()));
+    }
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                document_keys(item, path.clone(), keys);
+            }
+        }
+        Value::Object(map) => {
+            for (key, value) in map {
+                let path = match path.is_empty() {
+                    true => key.clone(),
+                    false => format!("{path}.{key}"),
+                };
+                document_keys(value, path, keys);
+            }
+        }
+        value => {
+            if let Some(term) = term(value) {
+                keys.insert(QueryKey::Term(path, term));
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use serde_json::Value;
+
+/// Something a document has, which a query may require.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(super) enum QueryKey {
+    /// A field with a value other than `null`.
+    Field(String),
+    /// A field equal to a string, number or boolean, see [`term`].
+    Term(String, String),
+}
+
+/// Text of a value for [`QueryKey::Term`], numbers equal as `f64` have the
+/// same text like [`crate::Evaluator`] compares them, `-0.0` is `0.0`.
+pub(super) fn term(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(format!("s{text}")),
+        Value::Number(number) => Some(format!("n{}", number.as_f64()? + 0.0)),
+        Value::Bool(boolean) => Some(format!("b{boolean}")),
+        _ => None,
+    }
+}
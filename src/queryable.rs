@@ -0,0 +1,248 @@
+//! Documents the evaluator reads through field paths, JSON or Rust structs.
+//!
+//! [`Queryable::get_field`] returns the value at a path, split like the
+//! paths of a query, and follows the rules of JSON documents: a path walking
+//! through a collection projects the field of each element, a number
+//! segment indexes it, and a missing field is `None`.
+//!
+//! `#[derive(Queryable)]` implements it for a struct with named fields whose
+//! types are `Queryable`, so a `Vec<Product>` is filtered without turning
+//! each product into JSON:
+//!
+//! ```text
+//! #[derive(Queryable)]
+//! struct Product {
+//!     name: String,
+//!     price: f64,
+//!     #[queryable(rename = "tags")]
+//!     labels: Vec<String>,
+//!     #[queryable(skip)]
+//!     cost: f64,
+//! }
+//! ```
+//!
+//! [`Queryable::has_field`] tells the paths of a type, and
+//! [`crate::Evaluator::filter`] and [`crate::Evaluator::matches`] fail with
+//! [`crate::evaluator::EvaluatorError::UnknownField`] on a path that isn't a
+//! field of the struct, or on a default field when the query has free text.
+//! JSON documents have every path.
+//!
+//! The derive refers to the trait as `::ql::queryable::Queryable`, so the
+//! crate must be a dependency named `ql`, not renamed in `Cargo.toml` nor
+//! used through a crate re-exporting it.
+
+mod value_ref;
+
+pub use self::value_ref::ValueRef;
+pub use ql_derive::Queryable;
+
+use crate::evaluator::lookup;
+use serde_json::{Number, Value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+
+pub trait Queryable {
+    /// Value at `path`, the document itself for an empty path.
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>>;
+
+    /// Whether `path` may name a field of the type.
+    fn has_field(_path: &[String]) -> bool {
+        true
+    }
+}
+
+impl Queryable for Value {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        let mut value = self;
+        for (position, segment) in path.iter().enumerate() {
+            value = match value {
+                Value::Object(map) => map.get(segment)?,
+                Value::Array(items) => match segment.parse::<usize>() {
+                    Ok(index) => items.get(index)?,
+                    Err(_) => {
+                        return Some(ValueRef::Json(Cow::Owned(lookup(value, &path[position..]))))
+                    }
+                },
+                _ => return None,
+            };
+        }
+        (!value.is_null()).then_some(ValueRef::Json(Cow::Borrowed(value)))
+    }
+}
+
+impl<T: Queryable + ?Sized> Queryable for &T {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        (**self).get_field(path)
+    }
+
+    fn has_field(path: &[String]) -> bool {
+        T::has_field(path)
+    }
+}
+
+impl<T: Queryable> Queryable for Box<T> {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        (**self).get_field(path)
+    }
+
+    fn has_field(path: &[String]) -> bool {
+        T::has_field(path)
+    }
+}
+
+impl<T: Queryable> Queryable for Option<T> {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        self.as_ref()?.get_field(path)
+    }
+
+    fn has_field(path: &[String]) -> bool {
+        T::has_field(path)
+    }
+}
+
+impl<T: Queryable> Queryable for Vec<T> {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        self.as_slice().get_field(path)
+    }
+
+    fn has_field(path: &[String]) -> bool {
+        <[T]>::has_field(path)
+    }
+}
+
+impl<T: Queryable> Queryable for [T] {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        let Some((segment, rest)) = path.split_first() else {
+            let items = self.iter().map(|item| item.get_field(&[]));
+            return Some(ValueRef::Array(
+                items.map(|item| item.unwrap_or(ValueRef::Null)).collect(),
+            ));
+        };
+        if let Ok(index) = segment.parse::<usize>() {
+            return self.get(index)?.get_field(rest);
+        }
+        let mut projection = vec![];
+        for item in self {
+            match item.get_field(path) {
+                None | Some(ValueRef::Null) => {}
+                Some(ValueRef::Array(values)) => projection.extend(values),
+                Some(ValueRef::Json(Cow::Borrowed(Value::Array(values)))) => projection.extend(
+                    values
+                        .iter()
+                        .map(|value| ValueRef::Json(Cow::Borrowed(value))),
+                ),
+                Some(ValueRef::Json(Cow::Owned(Value::Array(values)))) => projection.extend(
+                    values
+                        .into_iter()
+                        .map(|value| ValueRef::Json(Cow::Owned(value))),
+                ),
+                Some(value) => projection.push(value),
+            }
+        }
+        Some(ValueRef::Array(projection))
+    }
+
+    fn has_field(path: &[String]) -> bool {
+        match path.split_first() {
+            Some((segment, rest)) if segment.parse::<usize>().is_ok() => T::has_field(rest),
+            _ => T::has_field(path),
+        }
+    }
+}
+
+impl<T: Queryable> Queryable for HashMap<String, T> {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        match path.split_first() {
+            None => Some(ValueRef::Object(
+                self.iter()
+                    .map(|(key, value)| {
+                        (key.as_str(), value.get_field(&[]).unwrap_or(ValueRef::Null))
+                    })
+                    .collect(),
+            )),
+            Some((segment, rest)) => self.get(segment)?.get_field(rest),
+        }
+    }
+}
+
+impl<T: Queryable> Queryable for BTreeMap<String, T> {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        match path.split_first() {
+            None => Some(ValueRef::Object(
+                self.iter()
+                    .map(|(key, value)| {
+                        (key.as_str(), value.get_field(&[]).unwrap_or(ValueRef::Null))
+                    })
+                    .collect(),
+            )),
+            Some((segment, rest)) => self.get(segment)?.get_field(rest),
+        }
+    }
+}
+
+impl Queryable for str {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        path.is_empty()
+            .then_some(ValueRef::String(Cow::Borrowed(self)))
+    }
+
+    fn has_field(path: &[String]) -> bool {
+        path.is_empty()
+    }
+}
+
+impl Queryable for String {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        self.as_str().get_field(path)
+    }
+
+    fn has_field(path: &[String]) -> bool {
+        path.is_empty()
+    }
+}
+
+impl Queryable for bool {
+    fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+        path.is_empty().then_some(ValueRef::Bool(*self))
+    }
+
+    fn has_field(path: &[String]) -> bool {
+        path.is_empty()
+    }
+}
+
+macro_rules! integer {
+    ($($type:ty),*) => {
+        $(impl Queryable for $type {
+            fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+                path.is_empty().then(|| ValueRef::Number(Number::from(*self)))
+            }
+
+            fn has_field(path: &[String]) -> bool {
+                path.is_empty()
+            }
+        })*
+    };
+}
+
+integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! float {
+    ($($type:ty),*) => {
+        $(impl Queryable for $type {
+            fn get_field(&self, path: &[String]) -> Option<ValueRef<'_>> {
+                if !path.is_empty() {
+                    return None;
+                }
+                // NaN and infinities are `null` like in JSON.
+                Some(Number::from_f64(f64::from(*self)).map_or(ValueRef::Null, ValueRef::Number))
+            }
+
+            fn has_field(path: &[String]) -> bool {
+                path.is_empty()
+            }
+        })*
+    };
+}
+
+float!(f32, f64);
@@ -0,0 +1,35 @@
+use serde_json::{Map, Number, Value};
+use std::borrow::Cow;
+
+/// Value of a field of a [`super::Queryable`], borrowing what it can from
+/// the document. The evaluator converts it into a JSON [`Value`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueRef<'a> {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(Cow<'a, str>),
+    Array(Vec<ValueRef<'a>>),
+    Object(Vec<(&'a str, ValueRef<'a>)>),
+    /// Part of a JSON document.
+    Json(Cow<'a, Value>),
+}
+
+impl From<ValueRef<'_>> for Value {
+    fn from(value: ValueRef<'_>) -> Self {
+        match value {
+            ValueRef::Null => Value::Null,
+            ValueRef::Bool(value) => Value::Bool(value),
+            ValueRef::Number(number) => Value::Number(number),
+            ValueRef::String(text) => Value::String(text.into_owned()),
+            ValueRef::Array(items) => Value::Array(items.into_iter().map(Value::from).collect()),
+            ValueRef::Object(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(key, value)| (key.to_string(), Value::from(value)))
+                    .collect::<Map<_, _>>(),
+            ),
+            ValueRef::Json(value) => value.into_owned(),
+        }
+    }
+}
@@ -38,6 +38,27 @@ impl Tokenizer {
         let ref numeric_matches_fn = |source_cursor: &SourceCursor| {
             matches!(source_cursor.get_current_char(), '0'..='9' | '_')
         };
+        let date_matches_fn = |source_cursor: &SourceCursor| {
+            let chunk: Vec<char> = source_cursor.next_string(10).chars().collect();
+            chunk.len() == 10
+                && chunk.iter().enumerate().all(|(i, c)| match i {
+                    4 | 7 => *c == '-',
+                    _ => c.is_ascii_digit(),
+                })
+        };
+        let wildcard_matches_fn = |source_cursor: &SourceCursor| {
+            let run: Vec<char> = source_cursor
+                .source
+                .chars()
+                .skip(source_cursor.pos)
+                .take_while(
+                    |c| matches!(c, '_'|'$'|'0'..='9'|'a' ..= 'z' | 'A' ..= 'Z' | '*' | '?'),
+                )
+                .collect();
+            run.iter().any(|c| matches!(c, '*' | '?'))
+                && run.iter().any(|c| !matches!(c, '*' | '?'))
+        };
+        let wildcard_char_matches_fn = |source_cursor: &SourceCursor| matches!(source_cursor.get_current_char(), '_'|'$'|'0'..='9'|'a' ..= 'z' | 'A' ..= 'Z' | '*' | '?');
         let ref dot_matches_fn =
             |source_cursor: &SourceCursor| matches!(source_cursor.get_current_char(), '.');
         let ref operation_matches_fn = |source_cursor: &SourceCursor| {
@@ -46,6 +67,8 @@ impl Tokenizer {
                 '*' | '-' | '/' | '+' | '|' | '&' | '>' | '<'
             )
         };
+        let regex_matches_fn =
+            |source_cursor: &SourceCursor| matches!(source_cursor.get_current_char(), '/');
         let ref string_matches_fn =
             |source_cursor: &SourceCursor| matches!(source_cursor.get_current_char(), '\"');
         let ref colon_matches_fn =
@@ -58,6 +81,8 @@ impl Tokenizer {
             |source_cursor: &SourceCursor| matches!(source_cursor.get_current_char(), '(');
         let ref close_parenthesis_matches_fn =
             |source_cursor: &SourceCursor| matches!(source_cursor.get_current_char(), ')');
+        let comma_matches_fn =
+            |source_cursor: &SourceCursor| matches!(source_cursor.get_current_char(), ',');
         let ref equal_matches_fn =
             |source_cursor: &SourceCursor| matches!(source_cursor.get_current_char(), '=');
         let ref open_square_bracket_matches_fn =
@@ -171,7 +196,7 @@ impl Tokenizer {
 
             if Tokenizer::lexer_model_by_char_test(source_cursor, open_parenthesis_matches_fn) {
                 tokens.push(source_cursor.create_token(
-                    "open_curly_bracket",
+                    "open_parenthesis",
                     Direction::Current,
                     Direction::Next(1),
                 ));
@@ -230,6 +255,58 @@ impl Tokenizer {
                 tokens.extend(response_tokens);
                 continue;
             }
+            if Tokenizer::lexer_model_by_char_test(source_cursor, comma_matches_fn) {
+                tokens.push(source_cursor.create_token(
+                    "comma",
+                    Direction::Current,
+                    Direction::Next(1),
+                ));
+                continue;
+            }
+            if source_cursor.get(0, 2) == "=>" {
+                tokens.push(source_cursor.create_token("arrow", 0, 2));
+                continue;
+            }
+            if matches!(source_cursor.get(0, 2), "=~" | "!~") {
+                tokens.push(source_cursor.create_token("operation", 0, 2));
+                continue;
+            }
+            // `~1` right after a term, the number of edits of a fuzzy term or
+            // of words between the words of a phrase.
+            if let Some(len) = Tokenizer::modifier_len(source_cursor, '~', |c| c.is_ascii_digit()) {
+                let token =
+                    source_cursor.create_token("fuzzy", Direction::Current, Direction::Next(len));
+                if token.raw.len() > 1 && token.raw[1..].parse::<usize>().is_err() {
+                    return Err(LexerError::ModifierInvalid(token.raw, token.span));
+                }
+                tokens.push(token);
+                continue;
+            }
+            // `^2` or `^0.5` right after a term, its boost when scoring.
+            if let Some(len) =
+                Tokenizer::modifier_len(source_cursor, '^', |c| c.is_ascii_digit() || c == '.')
+            {
+                let token =
+                    source_cursor.create_token("boost", Direction::Current, Direction::Next(len));
+                if token.raw[1..].parse::<f64>().is_err() {
+                    return Err(LexerError::ModifierInvalid(token.raw, token.span));
+                }
+                tokens.push(token);
+                continue;
+            }
+            if source_cursor.get(0, 1) == "!" {
+                tokens.push(source_cursor.create_token("operation", 0, 1));
+                continue;
+            }
+            // `price>-1`, a comparison followed by a negative number.
+            if matches!(source_cursor.get(0, 2), ">-" | "<-") {
+                tokens.push(source_cursor.create_token("operation", 0, 1));
+                continue;
+            }
+            if matches!(source_cursor.get(0, 2), ">=" | "<=") {
+                tokens.push(source_cursor.create_token("operation", 0, 2));
+                continue;
+            }
             if Tokenizer::lexer_model_by_char_test(source_cursor, equal_matches_fn) {
                 let response_tokens = Tokenizer::lexer_model_by_char_bind(
                     source_cursor,
@@ -251,6 +328,15 @@ impl Tokenizer {
                 continue;
             }
 
+            // `..`, with a `<` on the side of each excluded bound.
+            if let Some(range) = ["<..<", "<..", "..<", ".."]
+                .into_iter()
+                .find(|range| source_cursor.get(0, range.len() as i32) == *range)
+            {
+                tokens.push(source_cursor.create_token("range", 0, range.len() as i32));
+                continue;
+            }
+
             if Tokenizer::lexer_model_by_char_test(source_cursor, dot_matches_fn) {
                 let response_tokens = Tokenizer::lexer_model_by_char_bind(
                     source_cursor,
@@ -262,6 +348,19 @@ impl Tokenizer {
                 continue;
             }
 
+            // `lapt*` or `col?r`, a word with wildcards, while a lone `*` is
+            // an operation.
+            if Tokenizer::lexer_model_by_char_test(source_cursor, wildcard_matches_fn) {
+                let response_tokens = Tokenizer::lexer_model_by_char_bind(
+                    source_cursor,
+                    "wildcard",
+                    wildcard_char_matches_fn,
+                    None,
+                )?;
+                tokens.extend(response_tokens);
+                continue;
+            }
+
             if Tokenizer::lexer_model_by_char_test(source_cursor, open_keyword_matches_fn) {
                 let response_tokens = Tokenizer::lexer_model_by_char_bind(
                     source_cursor,
@@ -273,6 +372,11 @@ impl Tokenizer {
                 continue;
             }
 
+            if Tokenizer::lexer_model_by_char_test(source_cursor, date_matches_fn) {
+                tokens.push(source_cursor.create_token("date", 0, 10));
+                continue;
+            }
+
             if Tokenizer::lexer_model_by_char_test(source_cursor, open_numeric_matches_fn) {
                 let response_tokens = Tokenizer::lexer_model_by_char_bind(
                     source_cursor,
@@ -284,6 +388,21 @@ impl Tokenizer {
                 continue;
             }
 
+            // `/` starts a regex where a value is expected, else is division.
+            if Tokenizer::lexer_model_by_char_test(source_cursor, regex_matches_fn)
+                && Tokenizer::regex_allowed_after(tokens.last())
+            {
+                let len = Tokenizer::regex_len(source_cursor).ok_or(
+                    lexer_error::LexerError::SymbolInvalid(source_cursor.current()),
+                )?;
+                tokens.push(source_cursor.create_token(
+                    "regex",
+                    Direction::Current,
+                    Direction::Next(len),
+                ));
+                continue;
+            }
+
             if Tokenizer::lexer_model_by_char_test(source_cursor, operation_matches_fn) {
                 let response_tokens = Tokenizer::lexer_model_by_char_bind(
                     source_cursor,
@@ -333,6 +452,65 @@ impl Tokenizer {
         Ok(tokens)
     }
 
+    /// Length of `symbol` and the following characters of its value, when
+    /// `symbol` is written right after a term.
+    fn modifier_len(
+        source_cursor: &SourceCursor,
+        symbol: char,
+        value: fn(char) -> bool,
+    ) -> Option<usize> {
+        let mut chars = source_cursor
+            .source
+            .chars()
+            .skip(source_cursor.pos.checked_sub(1)?);
+        if chars.next().is_none_or(|c| c.is_whitespace()) || chars.next() != Some(symbol) {
+            return None;
+        }
+        Some(1 + chars.take_while(|c| value(*c)).count())
+    }
+
+    fn regex_allowed_after(token: Option<&Token>) -> bool {
+        token.is_none_or(|token| {
+            matches!(
+                token.kind.as_str(),
+                "operation"
+                    | "equal"
+                    | "colon"
+                    | "comma"
+                    | "arrow"
+                    | "newline"
+                    | "open_parenthesis"
+                    | "open_square_bracket"
+                    | "open_curly_bracket"
+                    | "template_start_expression"
+            )
+        })
+    }
+
+    /// Length of `/pattern/flags`, `None` if it isn't closed in the line. A
+    /// `/` escaped or in a class like `[/]` doesn't close it.
+    fn regex_len(source_cursor: &SourceCursor) -> Option<usize> {
+        let char_at = |len: usize| source_cursor.get_at_char(Some(source_cursor.pos + len));
+        let mut len = 1;
+        let mut in_class = false;
+        loop {
+            match char_at(len) {
+                '\\' => len += 1,
+                '[' => in_class = true,
+                ']' => in_class = false,
+                '/' if !in_class => break,
+                '\0' | '\r' | '\n' => return None,
+                _ => {}
+            }
+            len += 1;
+        }
+        len += 1;
+        while char_at(len).is_ascii_alphabetic() {
+            len += 1;
+        }
+        Some(len)
+    }
+
     fn lexer_model_by_char_test<F: Fn(&SourceCursor) -> bool>(
         source_cursor: &mut SourceCursor,
         matches_fn: F,
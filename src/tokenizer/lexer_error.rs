@@ -1,4 +1,10 @@
+use super::token::Span;
+
 #[derive(Debug)]
 pub enum LexerError {
     SymbolInvalid(Option<(usize, char)>),
+    /// The number of a `~` or `^` modifier doesn't parse, like a distance
+    /// too large for a `usize` or `quick^1.2.3`, with the modifier and its
+    /// span.
+    ModifierInvalid(String, Span),
 }
@@ -95,7 +95,7 @@ impl SourceCursor {
         current
     }
 
-    pub fn _next_string(&self, chunk_len: usize) -> String {
+    pub fn next_string(&self, chunk_len: usize) -> String {
         self.source.chars().skip(self.pos).take(chunk_len).collect()
     }
 
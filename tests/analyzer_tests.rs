@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod analyzer_tests {
+    use ql::analyzer::{Analyzer, TextAnalyzer, TokenFilter, WordTokenizer};
+
+    #[test]
+    fn analyzer_standard() {
+        let analyzer = TextAnalyzer::standard();
+        assert_eq!(
+            analyzer.analyze("Quick-Fox, 10.5 Café"),
+            vec!["quick", "fox", "10", "5", "café"]
+        );
+        assert_eq!(analyzer.normalize("Café"), "café");
+    }
+
+    #[test]
+    fn analyzer_english() {
+        let analyzer = TextAnalyzer::english();
+        assert_eq!(
+            analyzer.analyze("The Batteries of the Café were running and jumped"),
+            vec!["battery", "cafe", "were", "run", "jump"]
+        );
+        assert_eq!(
+            analyzer.analyze("sing need bus class caresses ponies laptops Straße Æon"),
+            vec!["sing", "need", "bus", "class", "caress", "pony", "laptop", "strasse", "aeon"]
+        );
+        assert_eq!(analyzer.normalize("The Cafés"), "the cafes");
+        assert_eq!(analyzer.words("Running Cafés"), vec!["running", "cafes"]);
+    }
+
+    #[test]
+    fn analyzer_whitespace() {
+        let mut analyzer = TextAnalyzer::new(WordTokenizer::Whitespace);
+        analyzer.add_filter(TokenFilter::Lowercase);
+        assert_eq!(
+            analyzer.analyze("E-Mail  address"),
+            vec!["e-mail", "address"]
+        );
+    }
+}
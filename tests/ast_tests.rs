@@ -23,4 +23,124 @@ mod ast_tests {
         let tokens = Tokenizer::lexer(payload).unwrap();
         assert_debug_snapshot!(AST::parse(tokens));
     }
+
+    #[test]
+    fn ast_parse_quantifier_1() {
+        let payload = r#"any(tags, t => t = "red")"#;
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_quantifier_2() {
+        let payload = "all(variants.stock > 0)";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_range_1() {
+        let payload = "price:10..* date:2024-01-01..2024-12-31";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_in_1() {
+        let payload = r#"status in ["open", "pending"]"#;
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_call_1() {
+        let payload = r#"starts_with(sku, "AB") len(tags) > 2"#;
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_not_1() {
+        let payload = "search -value !status:closed";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_boolean_keywords_1() {
+        let payload = "title:foo OR title:bar and not draft";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_select_1() {
+        let payload = "SELECT name, price AS p FROM cars WHERE price > 10 ORDER BY price DESC, name LIMIT 10 OFFSET 1";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_pipeline_1() {
+        let payload = "status:open | sort price desc, name | limit 10 | pick name, price AS p";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_object_1() {
+        let payload = "status:open | { name, price: $.pricing.amount, label: `${brand} ${model}` }";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_sort_1() {
+        let payload = "red sort:-price,name | sort date DESC NULLS FIRST";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_group_1() {
+        let payload =
+            "status:open | group by brand | count(), avg(price) AS average, distinct_count(tags)";
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_regex_1() {
+        let payload = r#"name =~ /^a\/b$/i"#;
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_wildcard_1() {
+        let payload = r#"lapt* brand:ac?e"#;
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_fuzzy_1() {
+        let payload = r#"colour~1 name:"colr"~"#;
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_phrase_1() {
+        let payload = r#"title:"quick fox" "quick fox"~3 name:"fox""#;
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
+
+    #[test]
+    fn ast_parse_boost_1() {
+        let payload = r#"quick^2 title:fox^0.5"#;
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        assert_debug_snapshot!(AST::parse(tokens));
+    }
 }
@@ -0,0 +1,243 @@
+#[macro_use]
+extern crate assert_matches;
+
+#[cfg(test)]
+mod bytecode_tests {
+    use insta::assert_debug_snapshot;
+    use ql::{
+        analyzer::TextAnalyzer,
+        ast::AST,
+        bytecode::{Instruction, Program, Quantifier, Vm},
+        evaluator::{EvaluatorError, EvaluatorOptions},
+        Tokenizer,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn compile(payload: &str) -> Result<Program, EvaluatorError> {
+        Program::compile(&AST::parse(Tokenizer::lexer(payload).unwrap()))
+    }
+
+    fn matches(payload: &str, document: &Value) -> Result<bool, EvaluatorError> {
+        let program = Program::decode(&compile(payload)?.encode())?;
+        Vm::new(&EvaluatorOptions::default()).matches(&program, document)
+    }
+
+    #[test]
+    fn bytecode_compile() {
+        assert_debug_snapshot!(compile(
+            r#"brand:acme (price > 10 OR -sale) any(tags, t => t = "red")"#
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn bytecode_round_trip() {
+        for payload in [
+            "",
+            r#"laptop lapt* colour~1 "red bag"~2"#,
+            r#"brand:ac* OR brand:bolt~1 title:"red bag""#,
+            r#"price:-10<..<20 status in ["open"] { "a": brand } = [brand, 1.5]"#,
+            r#"name =~ /^A/i name !~ "b" name =~ brand `${brand}-${price}` = "x""#,
+            r#"lower(brand) = "acme" any(tags, t => t = "red") all(variants.stock > 0)"#,
+        ] {
+            let program = compile(payload).unwrap();
+            let bytes = program.encode();
+            let decoded = Program::decode(&bytes).unwrap();
+            assert_eq!(decoded, program, "{payload}");
+            assert_eq!(decoded.encode(), bytes, "{payload}");
+        }
+    }
+
+    #[test]
+    fn bytecode_jumps() {
+        // `AND` jumps over its right side when the left one is false and
+        // `OR` when it is true, both to the instruction after them.
+        let program = compile("brand:acme (price > 10 OR tags:sale)").unwrap();
+        assert_eq!(program.instructions[3], Instruction::JumpIfFalse(13));
+        assert_eq!(program.instructions[8], Instruction::JumpIfTrue(13));
+        assert_eq!(program.instructions.len(), 13);
+
+        // A quantifier jumps to its end without elements, and back to the
+        // predicate right after its iteration for each next element.
+        let program = compile("any(tags, t => any(t.values, v => v = 1))").unwrap();
+        assert_matches!(program.instructions[1], Instruction::Iterate { end: 9, .. });
+        assert_matches!(program.instructions[3], Instruction::Iterate { end: 8, .. });
+        assert_eq!(program.instructions[7], Instruction::Next(4));
+        assert_eq!(program.instructions[8], Instruction::Next(2));
+    }
+
+    #[test]
+    fn bytecode_matches() {
+        let document = json!({
+            "title": "Red laptop bag",
+            "brand": "acme",
+            "price": 40,
+            "tags": ["red", "sale"],
+            "variants": [{ "size": "m", "stock": 2 }, { "size": "l", "stock": 0 }],
+        });
+        assert_matches!(matches("laptop lapt* lapto~1", &document), Ok(true));
+        assert_matches!(matches(r#""red bag"~1 "laptop bag""#, &document), Ok(true));
+        assert_matches!(
+            matches(r#"brand:ac* brand:acne~1 title:"laptop bag""#, &document),
+            Ok(true)
+        );
+        assert_matches!(matches("price:40..<50 price:30<..40", &document), Ok(true));
+        assert_matches!(matches("price:40<..50", &document), Ok(false));
+        assert_matches!(
+            matches(r#"brand =~ /^A/i brand =~ "cm" title !~ brand"#, &document),
+            Ok(true)
+        );
+        assert_matches!(
+            matches(
+                "any(variants.stock > 1) -all(variants.stock > 0)",
+                &document
+            ),
+            Ok(true)
+        );
+        assert_matches!(
+            matches(
+                r#"any(variants, variants.size = "m" && variants.stock > 1)"#,
+                &document
+            ),
+            Ok(true)
+        );
+        // Functions are looked up when they are called.
+        assert_matches!(
+            matches("none(tags, t => t = blue) OR missing(x)", &document),
+            Ok(true)
+        );
+        assert_matches!(
+            matches("missing(x)", &document),
+            Err(EvaluatorError::FunctionNotFound(_, _))
+        );
+        assert_matches!(
+            matches("brand =~ price", &document),
+            Err(EvaluatorError::InvalidRegex(_, _))
+        );
+    }
+
+    #[test]
+    fn bytecode_options() {
+        let mut options = EvaluatorOptions::default();
+        options
+            .set_default_fields(vec!["title", "body"])
+            .set_analyzer("title", Arc::new(TextAnalyzer::english()));
+        let vm = Vm::new(&options);
+        let matches = |payload: &str, document: &Value| {
+            let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+            let program = Program::compile_with_options(&ast, &options).unwrap();
+            let program = Program::decode(&program.encode()).unwrap();
+            vm.matches(&program, document).unwrap()
+        };
+
+        let document = json!({ "title": "Café Running Shoes", "body": "Café" });
+        assert!(matches("cafe cafe*", &document));
+        assert!(matches(r#""run shoe"~0"#, &document));
+        assert!(matches(r#"title:"running the shoes""#, &document));
+        assert!(!matches(r#"body:"cafe""#, &document));
+        assert!(!matches("cafe", &json!({ "body": "Café" })));
+
+        let mut options = EvaluatorOptions::default();
+        options.set_default_fields(Vec::<String>::new());
+        let ast = AST::parse(Tokenizer::lexer("laptop").unwrap());
+        let program = Program::compile_with_options(&ast, &options).unwrap();
+        assert_matches!(
+            Vm::new(&options).matches(&program, &json!({ "title": "laptop" })),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn bytecode_errors() {
+        assert_matches!(
+            compile("name =~ /(/"),
+            Err(EvaluatorError::InvalidRegex(_, _))
+        );
+        assert_matches!(
+            compile(r#"name =~ "(""#),
+            Err(EvaluatorError::InvalidRegex(_, _))
+        );
+        assert_matches!(
+            Program::decode(b"QLB1\x00"),
+            Err(EvaluatorError::InvalidProgram(_))
+        );
+
+        let mut bytes = compile("brand:acme").unwrap().encode();
+        assert_matches!(
+            Program::decode(&bytes[..bytes.len() - 1]),
+            Err(EvaluatorError::InvalidProgram(_))
+        );
+        // `PushConst(0)` of the last instruction pointing at a missing
+        // constant.
+        let last = bytes.len() - 1;
+        bytes[last] = 9;
+        assert_matches!(
+            Program::decode(&bytes),
+            Err(EvaluatorError::InvalidProgram(_))
+        );
+
+        let decode = |instructions: Vec<Instruction>| {
+            let mut program = compile("brand:acme").unwrap();
+            program.instructions = instructions;
+            program.paths = vec![vec!["tags".to_string()], vec!["y".to_string()]];
+            Program::decode(&program.encode())
+        };
+        let iterate = |end| Instruction::Iterate {
+            quantifier: Quantifier::Any,
+            prefix: 1,
+            end,
+        };
+        assert_matches!(
+            decode(vec![
+                Instruction::LoadField(0),
+                iterate(4),
+                Instruction::PushConst(0),
+                Instruction::Next(2),
+            ]),
+            Ok(_)
+        );
+        // Two iterations sharing the `Next` of the outer one, which would
+        // loop forever on `{"tags": [1, 2]}`.
+        let shared = vec![
+            Instruction::LoadField(0),
+            iterate(6),
+            Instruction::LoadField(0),
+            iterate(6),
+            Instruction::PushConst(0),
+            Instruction::Next(2),
+        ];
+        assert_matches!(
+            decode(shared.clone()),
+            Err(EvaluatorError::InvalidProgram(_))
+        );
+        let mut program = Program::default();
+        program.instructions = shared;
+        program.constants = vec![json!(true)];
+        program.paths = vec![vec!["tags".to_string()], vec!["y".to_string()]];
+        assert_matches!(
+            Vm::new(&EvaluatorOptions::default()).matches(&program, &json!({ "tags": [1, 2] })),
+            Err(EvaluatorError::InvalidProgram(_))
+        );
+        // A `Next` without iteration, one jumping back to another
+        // iteration and a jump out of the predicate.
+        for instructions in [
+            vec![Instruction::PushConst(0), Instruction::Next(0)],
+            vec![
+                Instruction::LoadField(0),
+                iterate(4),
+                Instruction::PushConst(0),
+                Instruction::Next(1),
+            ],
+            vec![
+                Instruction::LoadField(0),
+                iterate(5),
+                Instruction::PushConst(0),
+                Instruction::JumpIfTrue(5),
+                Instruction::Next(2),
+            ],
+        ] {
+            assert_matches!(decode(instructions), Err(EvaluatorError::InvalidProgram(_)));
+        }
+    }
+}
@@ -0,0 +1,140 @@
+#[macro_use]
+extern crate assert_matches;
+
+#[cfg(test)]
+mod codegen_tests {
+    use insta::assert_snapshot;
+    use ql::{
+        ast::AST,
+        codegen::{CodegenError, CodegenOptions, TypeScript},
+        functions::{Function, FunctionRegistry, FunctionSignature, ValueType},
+        Tokenizer,
+    };
+    use serde_json::Value;
+
+    fn typescript(payload: &str) -> String {
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        TypeScript::new(&CodegenOptions::default())
+            .generate(&AST::parse(tokens))
+            .unwrap()
+    }
+
+    #[test]
+    fn codegen_typescript_sample_3() {
+        assert_snapshot!(typescript(r#"search value condition: "value""#));
+    }
+
+    #[test]
+    fn codegen_typescript_sample_4() {
+        assert_snapshot!(typescript(
+            r#"
+            "search value"
+            condition: "value"
+            context.condition > "value"
+            "#
+        ));
+    }
+
+    #[test]
+    fn codegen_typescript_call() {
+        assert_snapshot!(typescript(
+            r#"lower(name) = "x" len(tags) > 2 starts_with(sku, "AB")"#
+        ));
+    }
+
+    #[test]
+    fn codegen_typescript_text() {
+        // Free text is contained in the field ignoring case, not a regex.
+        assert_snapshot!(typescript("Laptop 42"));
+    }
+
+    #[test]
+    fn codegen_typescript_call_template() {
+        let mut functions = FunctionRegistry::default();
+        functions.register(
+            "concat",
+            Function::new(
+                FunctionSignature::new(
+                    vec![ValueType::String, ValueType::String],
+                    ValueType::String,
+                ),
+                |_| Value::Null,
+            )
+            .with_translation("typescript", "({0} + {1} + \"{2}\")"),
+        );
+        let mut options = CodegenOptions::default();
+        options.set_functions(functions);
+        let tokens = Tokenizer::lexer(r#"concat("{1}", name) = "x""#).unwrap();
+        let code = TypeScript::new(&options)
+            .generate(&AST::parse(tokens))
+            .unwrap();
+        assert!(
+            code.contains(r#"("{1}" + item.name + "{2}") == "x""#),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn codegen_typescript_quantifier() {
+        assert_snapshot!(typescript(
+            r#"any(tags, t => t = "red") all(variants.stock > 0) status in ["open"] price:10..*"#
+        ));
+    }
+
+    #[test]
+    fn codegen_typescript_range() {
+        assert_snapshot!(typescript("price:-10<..20 stock:0..<5"));
+    }
+
+    #[test]
+    fn codegen_typescript_not() {
+        assert_snapshot!(typescript("search -value NOT status:closed"));
+    }
+
+    #[test]
+    fn codegen_typescript_sort() {
+        assert_snapshot!(typescript(
+            "status:open sort:-price | sort lower(name) NULLS FIRST"
+        ));
+    }
+
+    #[test]
+    fn codegen_typescript_regex() {
+        assert_snapshot!(typescript(r#"name =~ /^a\/b[/]$/i sku !~ /x/"#));
+    }
+
+    #[test]
+    fn codegen_typescript_regex_invalid() {
+        let tokens = Tokenizer::lexer("name =~ /(a/").unwrap();
+        assert_matches!(
+            TypeScript::new(&CodegenOptions::default()).generate(&AST::parse(tokens)),
+            Err(CodegenError::InvalidRegex(_, span)) if span.start == 8 && span.end == 12
+        );
+    }
+
+    #[test]
+    fn codegen_typescript_wildcard() {
+        assert_snapshot!(typescript(r#"lapt* brand:ac?e -col?r"#));
+    }
+
+    #[test]
+    fn codegen_typescript_fuzzy() {
+        let tokens = Tokenizer::lexer("name:colour~1").unwrap();
+        assert_matches!(
+            TypeScript::new(&CodegenOptions::default()).generate(&AST::parse(tokens)),
+            Err(CodegenError::StatementNotSupported(span)) if span.start == 5 && span.end == 13
+        );
+    }
+
+    #[test]
+    fn codegen_typescript_phrase() {
+        assert_snapshot!(typescript(r#"title:"quick fox" "lazy dog"~0"#));
+
+        let tokens = Tokenizer::lexer(r#""quick fox"~3"#).unwrap();
+        assert_matches!(
+            TypeScript::new(&CodegenOptions::default()).generate(&AST::parse(tokens)),
+            Err(CodegenError::StatementNotSupported(_))
+        );
+    }
+}
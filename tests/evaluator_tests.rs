@@ -0,0 +1,1184 @@
+#[macro_use]
+extern crate assert_matches;
+
+#[cfg(test)]
+mod evaluator_tests {
+    use ql::{
+        analyzer::TextAnalyzer,
+        ast::{ParserOptions, AST},
+        evaluator::{
+            CompiledQuery, Cursor, EvaluatorError, EvaluatorOptions, FacetOptions, Facets,
+            RankOptions, SnippetOptions,
+        },
+        functions::{Function, FunctionRegistry, FunctionSignature, ValueType},
+        Evaluator, Tokenizer,
+    };
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn matches(payload: &str, document: &Value) -> bool {
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        Evaluator::default()
+            .matches(&AST::parse(tokens), document)
+            .unwrap()
+    }
+
+    #[test]
+    fn evaluator_text() {
+        let document = json!({ "title": "A search value here" });
+        assert!(matches("search value", &document));
+        assert!(matches(r#""SEARCH VALUE""#, &document));
+        assert!(!matches("value search", &document));
+    }
+
+    #[test]
+    fn evaluator_text_default_fields() {
+        let document = json!({ "name": "Red car", "title": "Blue" });
+        let evaluator =
+            Evaluator::new(EvaluatorOptions::default().set_default_fields(vec!["name"]));
+        let ast = AST::parse(Tokenizer::lexer("red").unwrap());
+        assert!(evaluator.matches(&ast, &document).unwrap());
+    }
+
+    #[test]
+    fn evaluator_comparison() {
+        let document = json!({
+            "title": "search value",
+            "condition": "value",
+            "context": { "condition": "w", "price": 10.5 },
+        });
+        assert!(matches(r#"search value condition: "value""#, &document));
+        assert!(matches("condition:value", &document));
+        assert!(matches(
+            r#"
+            "search value"
+            condition: "value"
+            context.condition > "value"
+            "#,
+            &document
+        ));
+        assert!(matches("$.context.price > 10.25", &document));
+        assert!(!matches("$.context.price < 10", &document));
+        assert!(!matches(r#"condition: "other""#, &document));
+    }
+
+    #[test]
+    fn evaluator_array_shorthand() {
+        let document = json!({ "tags": ["red", "blue"] });
+        assert!(matches(r#"tags:"red""#, &document));
+        assert!(matches("tags:blue", &document));
+        assert!(!matches(r#"tags:"green""#, &document));
+    }
+
+    #[test]
+    fn evaluator_quantifier_any_binding() {
+        let document = json!({ "tags": ["red", "blue"] });
+        assert!(matches(r#"any(tags, t => t = "red")"#, &document));
+        assert!(!matches(r#"any(tags, t => t = "green")"#, &document));
+        assert!(matches(r#"none(tags, t => t = "green")"#, &document));
+        assert!(!matches(r#"all(tags, t => t = "red")"#, &document));
+    }
+
+    #[test]
+    fn evaluator_quantifier_implicit() {
+        let document = json!({
+            "variants": [{ "stock": 3, "size": "m" }, { "stock": 0, "size": "l" }],
+        });
+        assert!(!matches("all(variants.stock > 0)", &document));
+        assert!(matches("any(variants.stock > 0)", &document));
+        assert!(matches(
+            r#"any(variants, variants.stock > 0 && variants.size = "m")"#,
+            &document
+        ));
+        assert!(!matches(
+            r#"any(variants, variants.stock > 0 && variants.size = "l")"#,
+            &document
+        ));
+        assert!(matches(
+            r#"variants.stock > 0 && variants.size = "l""#,
+            &document
+        ));
+        assert!(matches(
+            r#"any(variants, v => v.stock > 0 && v.size = "m")"#,
+            &document
+        ));
+    }
+
+    #[test]
+    fn evaluator_quantifier_scalar_and_missing() {
+        let document = json!({ "tags": "red", "variants": [] });
+        assert!(matches(r#"any(tags, t => t = "red")"#, &document));
+        assert!(matches(r#"all(tags, t => t = "red")"#, &document));
+        assert!(matches("all(variants.stock > 0)", &document));
+        assert!(!matches("any(variants.stock > 0)", &document));
+        assert!(!matches(r#"any(missing, m => m = "red")"#, &document));
+        assert!(matches(r#"none(missing, m => m = "red")"#, &document));
+    }
+
+    #[test]
+    fn evaluator_in() {
+        let document = json!({ "status": "open", "tags": ["red", "blue"] });
+        assert!(matches(r#"status in ["open", "pending"]"#, &document));
+        assert!(!matches(r#"status in ["closed"]"#, &document));
+        assert!(matches(r#"tags in ["green", "blue"]"#, &document));
+        assert!(!matches("status in []", &document));
+    }
+
+    #[test]
+    fn evaluator_range() {
+        let document = json!({ "price": 20, "sizes": [38, 44] });
+        assert!(matches("price:10..20", &document));
+        assert!(matches("price:20..30", &document));
+        assert!(!matches("price:20.5..30", &document));
+        assert!(matches("price:10..*", &document));
+        assert!(matches("price:*..20", &document));
+        assert!(!matches("price:*..19.99", &document));
+        assert!(matches("sizes:40..44", &document));
+        assert!(!matches("sizes:39..43", &document));
+    }
+
+    #[test]
+    fn evaluator_range_exclusive() {
+        let document = json!({ "price": 20 });
+        assert!(matches("price:10<..20", &document));
+        assert!(!matches("price:20<..30", &document));
+        assert!(matches("price:20..<30", &document));
+        assert!(!matches("price:10..<20", &document));
+        assert!(!matches("price:20<..<30", &document));
+        assert!(matches("price:19<..<21", &document));
+        assert!(matches("price:10<..*", &document));
+        assert!(!matches("price:*..<20", &document));
+    }
+
+    #[test]
+    fn evaluator_range_negative() {
+        let document = json!({ "price": -5, "delta": [-1.5, 3] });
+        assert!(matches("price:-10..0", &document));
+        assert!(!matches("price:-4..0", &document));
+        assert!(!matches("price:-10..<-5", &document));
+        assert!(matches("price > -10", &document));
+        assert!(!matches("price > -1", &document));
+        assert!(matches("price:>-6", &document));
+        assert!(matches("price:<=-5", &document));
+        assert!(matches("price = -5", &document));
+        assert!(matches("delta:-2..-1", &document));
+    }
+
+    #[test]
+    fn evaluator_range_comparison() {
+        let document = json!({ "price": 10 });
+        assert!(matches("price:>=10", &document));
+        assert!(!matches("price:>10", &document));
+        assert!(matches("price:<=10 price:>9", &document));
+        assert!(matches("price >= 10", &document));
+        assert!(!matches("price < 10", &document));
+    }
+
+    #[test]
+    fn evaluator_date_range() {
+        let document = json!({ "date": "2024-05-17" });
+        assert!(matches("date:2024-01-01..2024-12-31", &document));
+        assert!(matches("date:>=2024-05-17", &document));
+        assert!(!matches("date:2023-01-01..2023-12-31", &document));
+    }
+
+    #[test]
+    fn evaluator_call() {
+        let document = json!({ "name": "ABC", "sku": "AB-12", "tags": ["a", "b", "c"] });
+        assert!(matches(r#"lower(name) = "abc""#, &document));
+        assert!(matches("len(tags) > 2", &document));
+        assert!(matches(r#"starts_with(sku, "AB")"#, &document));
+        assert!(!matches(r#"starts_with(sku, "12")"#, &document));
+        assert!(!matches(r#"lower(missing) = "abc""#, &document));
+    }
+
+    #[test]
+    fn evaluator_call_registered() {
+        let mut functions = FunctionRegistry::new();
+        functions.register(
+            "double",
+            Function::new(
+                FunctionSignature::new(vec![ValueType::Number], ValueType::Number),
+                |arguments| (arguments[0].as_f64().unwrap() * 2.0).into(),
+            ),
+        );
+        let evaluator = Evaluator::new(EvaluatorOptions::default().set_functions(functions));
+        let document = json!({ "price": 4 });
+
+        let ast = AST::parse(Tokenizer::lexer("double(price) = 8").unwrap());
+        assert!(evaluator.matches(&ast, &document).unwrap());
+
+        let ast = AST::parse(Tokenizer::lexer(r#"lower(name) = "abc""#).unwrap());
+        assert_matches!(
+            evaluator.matches(&ast, &document),
+            Err(EvaluatorError::FunctionNotFound(..))
+        );
+
+        let ast = AST::parse(Tokenizer::lexer("double(price, 2) = 8").unwrap());
+        assert_matches!(
+            evaluator.matches(&ast, &document),
+            Err(EvaluatorError::FunctionArity(..))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "aggregate")]
+    fn evaluator_call_registered_aggregate() {
+        FunctionRegistry::new().register(
+            "max",
+            Function::new(
+                FunctionSignature::new(vec![ValueType::Number], ValueType::Number),
+                |arguments| arguments[0].clone(),
+            ),
+        );
+    }
+
+    #[test]
+    fn evaluator_not() {
+        let document = json!({ "title": "red car", "status": "open" });
+        assert!(matches("red -blue", &document));
+        assert!(!matches("red -car", &document));
+        assert!(matches("!status:closed", &document));
+        assert!(!matches("NOT status:open", &document));
+        assert!(matches("-missing:value", &document));
+        assert!(matches("red - car", &json!({ "title": "red - car" })));
+    }
+
+    #[test]
+    fn evaluator_not_precedence() {
+        let document = json!({ "title": "red car", "status": "open" });
+        assert!(matches("NOT status:closed red", &document));
+        assert!(!matches("NOT status:open red", &document));
+        assert!(matches("!status:open || red", &document));
+        assert!(!matches("!(status:open red)", &document));
+    }
+
+    #[test]
+    fn evaluator_boolean_keywords() {
+        let document = json!({ "title": "foo", "status": "open" });
+        assert!(matches("title:foo OR title:bar", &document));
+        assert!(matches("title:bar or title:foo", &document));
+        assert!(!matches("title:foo AND title:bar", &document));
+        assert!(matches("title:foo And status:open", &document));
+        assert!(matches("not title:bar", &document));
+        assert!(matches("status:closed OR foo", &document));
+    }
+
+    #[test]
+    fn evaluator_boolean_keywords_literal() {
+        let document = json!({ "title": "this OR that" });
+        assert!(matches(r#"this "OR" that"#, &document));
+        assert!(!matches("this OR that", &json!({ "title": "x" })));
+        assert!(matches("this OR", &document));
+
+        let evaluator = Evaluator::default();
+        let mut options = ParserOptions::default();
+        options.set_boolean_keywords(false);
+        let ast = AST::parse_with_options(Tokenizer::lexer("this OR that").unwrap(), &options);
+        assert!(evaluator.matches(&ast, &document).unwrap());
+        assert!(!evaluator
+            .matches(&ast, &json!({ "title": "this" }))
+            .unwrap());
+    }
+
+    fn cars() -> HashMap<String, Vec<Value>> {
+        HashMap::from([(
+            "cars".to_string(),
+            vec![
+                json!({ "name": "Red car", "brand": "a", "price": 20 }),
+                json!({ "name": "Blue car", "brand": "b", "price": 5 }),
+                json!({ "name": "Green car", "brand": "a", "price": 15 }),
+                json!({ "name": "Black car", "brand": "b", "price": 15 }),
+            ],
+        )])
+    }
+
+    fn select(payload: &str) -> Result<Vec<Value>, EvaluatorError> {
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        Evaluator::default().select(&AST::parse(tokens), &cars())
+    }
+
+    #[test]
+    fn evaluator_select() {
+        assert_eq!(select("SELECT * FROM cars").unwrap(), cars()["cars"]);
+        assert_eq!(
+            select("select name, price as p from cars where price > 10 and brand:a").unwrap(),
+            vec![
+                json!({ "name": "Red car", "p": 20 }),
+                json!({ "name": "Green car", "p": 15 }),
+            ]
+        );
+        assert_eq!(
+            select("SELECT name FROM cars WHERE price < 100 ORDER BY price DESC, name LIMIT 2 OFFSET 1")
+                .unwrap(),
+            vec![
+                json!({ "name": "Black car" }),
+                json!({ "name": "Green car" })
+            ]
+        );
+        // Counts too large for a `usize` take every row.
+        assert_eq!(
+            select("SELECT * FROM cars LIMIT 99999999999999999999999").unwrap(),
+            cars()["cars"]
+        );
+        assert!(
+            select("SELECT * FROM cars OFFSET 99_999_999_999_999_999_999")
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            select("SELECT name FROM cars ORDER BY price DESC").unwrap(),
+            vec![
+                json!({ "name": "Red car" }),
+                json!({ "name": "Green car" }),
+                json!({ "name": "Black car" }),
+                json!({ "name": "Blue car" }),
+            ]
+        );
+        assert_matches!(
+            select("SELECT * FROM boats"),
+            Err(EvaluatorError::CollectionNotFound(name, _)) if name == "boats"
+        );
+    }
+
+    fn pipeline(payload: &str) -> Result<Vec<Value>, EvaluatorError> {
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        let ast = AST::parse(tokens);
+        Evaluator::default()
+            .pipeline(&ast, cars()["cars"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn evaluator_pipeline() {
+        assert_eq!(
+            pipeline("brand:b | pick name").unwrap(),
+            vec![
+                json!({ "name": "Blue car" }),
+                json!({ "name": "Black car" })
+            ]
+        );
+        assert_eq!(
+            pipeline("price > 10 | sort price, name desc | limit 2 | pick name, price AS p")
+                .unwrap(),
+            vec![
+                json!({ "name": "Green car", "p": 15 }),
+                json!({ "name": "Black car", "p": 15 }),
+            ]
+        );
+        assert_eq!(
+            pipeline("price > 10 | pick name | limit 1").unwrap(),
+            vec![json!({ "name": "Red car" })]
+        );
+        assert!(pipeline("price > 100 | sort price").unwrap().is_empty());
+        // Counts too large for a `usize` take every document.
+        assert_eq!(
+            pipeline("| limit 99999999999999999999999").unwrap(),
+            cars()["cars"]
+        );
+        assert!(pipeline("| offset 99999999999999999999999")
+            .unwrap()
+            .is_empty());
+        assert_eq!(pipeline("brand:a").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn evaluator_pipeline_streaming() {
+        let documents = (0..).map(|n| json!({ "n": n }));
+        let ast = AST::parse(Tokenizer::lexer("n > 2 | limit 2 | pick n").unwrap());
+        let results: Result<Vec<Value>, _> =
+            Evaluator::default().pipeline(&ast, documents).collect();
+        assert_eq!(results.unwrap(), vec![json!({ "n": 3 }), json!({ "n": 4 })]);
+    }
+
+    #[test]
+    fn evaluator_projection() {
+        let documents = vec![
+            json!({ "name": "a1", "brand": "Acme", "model": "One", "pricing": { "amount": 10 } }),
+            json!({ "name": "b2", "brand": "Bolt", "model": 2 }),
+        ];
+        let ast = AST::parse(
+            Tokenizer::lexer(
+                r#"
+                | {
+                    name,
+                    price: $.pricing.amount,
+                    label: `${brand} ${model}`,
+                    "meta": { code: `\`${lower(name)}\`` }
+                }
+                "#,
+            )
+            .unwrap(),
+        );
+        let results: Result<Vec<Value>, _> =
+            Evaluator::default().pipeline(&ast, documents).collect();
+        assert_eq!(
+            results.unwrap(),
+            vec![
+                json!({ "name": "a1", "price": 10, "label": "Acme One", "meta": { "code": "`a1`" } }),
+                json!({ "name": "b2", "price": null, "label": "Bolt 2", "meta": { "code": "`b2`" } }),
+            ]
+        );
+    }
+
+    fn sorted(payload: &str, documents: &[Value]) -> Vec<Value> {
+        let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+        Evaluator::default()
+            .pipeline(&ast, documents.to_vec())
+            .map(|document| document.unwrap()["id"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn evaluator_sort() {
+        let documents = vec![
+            json!({ "id": 1, "price": 10, "name": "b" }),
+            json!({ "id": 2, "name": "a" }),
+            json!({ "id": 3, "price": "9", "name": "c" }),
+            json!({ "id": 4, "price": 2, "name": "a" }),
+            json!({ "id": 5, "price": null, "name": "d" }),
+            json!({ "id": 6, "price": 10, "name": "a" }),
+            json!({ "id": 7, "price": true, "name": "e" }),
+        ];
+        assert_eq!(
+            sorted("sort:price", &documents),
+            vec![
+                json!(7),
+                json!(4),
+                json!(1),
+                json!(6),
+                json!(3),
+                json!(2),
+                json!(5)
+            ]
+        );
+        assert_eq!(
+            sorted("sort:-price,name", &documents),
+            vec![
+                json!(3),
+                json!(6),
+                json!(1),
+                json!(4),
+                json!(7),
+                json!(2),
+                json!(5)
+            ]
+        );
+        assert_eq!(
+            sorted(
+                "| sort price DESC NULLS FIRST, name ASC NULLS LAST",
+                &documents
+            ),
+            vec![
+                json!(2),
+                json!(5),
+                json!(3),
+                json!(6),
+                json!(1),
+                json!(4),
+                json!(7)
+            ]
+        );
+        assert_eq!(
+            sorted("sort:name | sort price NULLS FIRST", &documents),
+            vec![
+                json!(2),
+                json!(5),
+                json!(7),
+                json!(4),
+                json!(6),
+                json!(1),
+                json!(3)
+            ]
+        );
+        assert!(matches("red sort:price", &json!({ "title": "red" })));
+    }
+
+    #[test]
+    fn evaluator_offset() {
+        assert_eq!(
+            pipeline("| sort price, name | offset 1 | limit 2 | pick name").unwrap(),
+            vec![
+                json!({ "name": "Black car" }),
+                json!({ "name": "Green car" })
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluator_page() {
+        let documents: Vec<Value> = (1..=7)
+            .map(|id| json!({ "id": id, "price": id % 3 }))
+            .collect();
+        let ast = AST::parse(
+            Tokenizer::lexer("id > 1 | sort price DESC, id | limit 3 | pick id").unwrap(),
+        );
+        let evaluator = Evaluator::default();
+
+        let mut pages = vec![];
+        let mut cursor = None;
+        loop {
+            let page = evaluator
+                .page(&ast, documents.clone(), cursor.as_ref())
+                .unwrap();
+            pages.push(page.documents);
+            match page.cursor {
+                Some(next) => cursor = Some(Cursor::decode(&next.encode()).unwrap()),
+                None => break,
+            }
+        }
+        assert_eq!(
+            pages,
+            vec![
+                vec![json!({ "id": 2 }), json!({ "id": 5 }), json!({ "id": 4 })],
+                vec![json!({ "id": 7 }), json!({ "id": 3 }), json!({ "id": 6 })],
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluator_cursor() {
+        let cursor = Cursor::new(vec![json!(10), json!("a/b?"), json!(null)]);
+        let encoded = cursor.encode();
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(Cursor::decode(&encoded).unwrap(), cursor);
+        assert_matches!(
+            Cursor::decode("not a cursor"),
+            Err(EvaluatorError::InvalidCursor(_))
+        );
+
+        let ast = AST::parse(Tokenizer::lexer("| sort price | limit 1").unwrap());
+        assert_matches!(
+            Evaluator::default().page(&ast, vec![], Some(&Cursor::new(vec![]))),
+            Err(EvaluatorError::InvalidCursor(_))
+        );
+    }
+
+    #[test]
+    fn evaluator_group() {
+        let documents = vec![
+            json!({ "brand": "a", "price": 10, "stock": 1, "tags": ["x", "y"] }),
+            json!({ "brand": "b", "price": 5, "tags": ["x"] }),
+            json!({ "brand": "a", "price": 15, "stock": 2, "tags": "y" }),
+            json!({ "brand": "a", "price": "n/a", "stock": 4 }),
+        ];
+        let ast = AST::parse(
+            Tokenizer::lexer(
+                "| group by brand | count(), avg(price), sum(stock) AS stock, min(price), max(price), count(stock), distinct_count(tags)",
+            )
+            .unwrap(),
+        );
+        let rows: Result<Vec<Value>, _> = Evaluator::default()
+            .pipeline(&ast, documents.clone())
+            .collect();
+        assert_eq!(
+            rows.unwrap(),
+            vec![
+                json!({
+                    "brand": "a",
+                    "count": 3,
+                    "avg_price": 12.5,
+                    "stock": 7,
+                    "min_price": 10,
+                    "max_price": 15,
+                    "count_stock": 3,
+                    "distinct_count_tags": 2,
+                }),
+                json!({
+                    "brand": "b",
+                    "count": 1,
+                    "avg_price": 5,
+                    "stock": 0,
+                    "min_price": 5,
+                    "max_price": 5,
+                    "count_stock": 0,
+                    "distinct_count_tags": 1,
+                }),
+            ]
+        );
+
+        let ast = AST::parse(Tokenizer::lexer("brand:c | count(), avg(price)").unwrap());
+        let rows: Result<Vec<Value>, _> = Evaluator::default().pipeline(&ast, documents).collect();
+        assert_eq!(
+            rows.unwrap(),
+            vec![json!({ "count": 0, "avg_price": null })]
+        );
+
+        // `1` and `1.0` are the same key and the same value.
+        let documents = vec![
+            json!({ "size": 1, "weight": 2.5 }),
+            json!({ "size": 1.0, "weight": 2 }),
+            json!({ "size": [1.0, 2], "weight": "heavy" }),
+            json!({ "size": [1, 2.0] }),
+        ];
+        let ast = AST::parse(
+            Tokenizer::lexer(
+                "| group by size | count(), distinct_count(size), min(weight), max(weight)",
+            )
+            .unwrap(),
+        );
+        let rows: Result<Vec<Value>, _> = Evaluator::default().pipeline(&ast, documents).collect();
+        assert_eq!(
+            rows.unwrap(),
+            vec![
+                json!({ "size": 1, "count": 2, "distinct_count_size": 1, "min_weight": 2, "max_weight": 2.5 }),
+                json!({ "size": [1.0, 2], "count": 2, "distinct_count_size": 2, "min_weight": null, "max_weight": null }),
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluator_select_group_by() {
+        assert_eq!(
+            select("SELECT brand, count() AS cars, sum(price) FROM cars WHERE price > 5 GROUP BY brand ORDER BY cars DESC, brand").unwrap(),
+            vec![
+                json!({ "brand": "a", "cars": 2, "sum_price": 35 }),
+                json!({ "brand": "b", "cars": 1, "sum_price": 15 }),
+            ]
+        );
+        assert_eq!(
+            select("SELECT max(price) FROM cars").unwrap(),
+            vec![json!({ "max_price": 20 })]
+        );
+        assert_eq!(
+            select("SELECT * FROM cars GROUP BY brand ORDER BY brand DESC LIMIT 1").unwrap(),
+            vec![json!({ "brand": "b" })]
+        );
+    }
+
+    fn facet_counts(facets: &Facets) -> Vec<(String, Vec<(Value, usize)>)> {
+        facets
+            .facets
+            .iter()
+            .map(|facet| {
+                let values = facet
+                    .values
+                    .iter()
+                    .map(|value| (value.value.clone(), value.count))
+                    .collect();
+                (facet.field.clone(), values)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn evaluator_facets() {
+        let documents = vec![
+            json!({ "brand": "acme", "price": 10, "tags": ["red", "new", "red"] }),
+            json!({ "brand": "bolt", "price": 20, "tags": ["red"] }),
+            json!({ "brand": "acme", "price": 30 }),
+            json!({ "brand": "core", "price": 40, "tags": "blue" }),
+        ];
+        let ast = AST::parse(Tokenizer::lexer("price < 45 (brand:acme OR brand:bolt)").unwrap());
+        let mut options = FacetOptions::default();
+        options.set_fields(vec!["brand", "tags"]);
+
+        let facets = Evaluator::default()
+            .facets(&ast, documents.clone(), &options)
+            .unwrap();
+        assert_eq!(facets.documents, documents[0..3]);
+        assert_eq!(
+            facet_counts(&facets),
+            vec![
+                (
+                    "brand".to_string(),
+                    vec![(json!("acme"), 2), (json!("bolt"), 1)]
+                ),
+                (
+                    "tags".to_string(),
+                    vec![(json!("red"), 2), (json!("new"), 1)]
+                ),
+            ]
+        );
+
+        // Brands are counted with `price < 45` alone, tags with the whole
+        // query as it has no condition on tags.
+        options.set_exclude_own_conditions(true);
+        let facets = Evaluator::default()
+            .facets(&ast, documents.clone(), &options)
+            .unwrap();
+        assert_eq!(facets.documents, documents[0..3]);
+        assert_eq!(
+            facet_counts(&facets),
+            vec![
+                (
+                    "brand".to_string(),
+                    vec![(json!("acme"), 2), (json!("bolt"), 1), (json!("core"), 1)]
+                ),
+                (
+                    "tags".to_string(),
+                    vec![(json!("red"), 2), (json!("new"), 1)]
+                ),
+            ]
+        );
+
+        // `AND` and `&&` join conditions like spaces.
+        for payload in [
+            "price > 5 brand:acme",
+            "price > 5 AND brand:acme",
+            "price > 5 && brand:acme",
+        ] {
+            let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+            let facets = Evaluator::default()
+                .facets(&ast, documents.clone(), &options)
+                .unwrap();
+            assert_eq!(
+                facets.documents,
+                vec![documents[0].clone(), documents[2].clone()],
+                "{payload}"
+            );
+            assert_eq!(
+                facet_counts(&facets)[0],
+                (
+                    "brand".to_string(),
+                    vec![(json!("acme"), 2), (json!("bolt"), 1), (json!("core"), 1)]
+                ),
+                "{payload}"
+            );
+        }
+
+        let ast = AST::parse(Tokenizer::lexer("price < 45 tags:red").unwrap());
+        let facets = Evaluator::default()
+            .facets(&ast, documents.clone(), &options)
+            .unwrap();
+        assert_eq!(facets.documents, documents[0..2]);
+        assert_eq!(
+            facet_counts(&facets),
+            vec![
+                (
+                    "brand".to_string(),
+                    vec![(json!("acme"), 1), (json!("bolt"), 1)]
+                ),
+                (
+                    "tags".to_string(),
+                    vec![(json!("red"), 2), (json!("blue"), 1), (json!("new"), 1)]
+                ),
+            ]
+        );
+
+        options.set_exclude_own_conditions(false);
+        let facets = Evaluator::default()
+            .facets(&ast, documents, &options)
+            .unwrap();
+        assert_eq!(
+            facet_counts(&facets),
+            vec![
+                (
+                    "brand".to_string(),
+                    vec![(json!("acme"), 1), (json!("bolt"), 1)]
+                ),
+                (
+                    "tags".to_string(),
+                    vec![(json!("red"), 2), (json!("new"), 1)]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluator_facets_numbers() {
+        let documents = vec![
+            json!({ "size": 1 }),
+            json!({ "size": 1.0 }),
+            json!({ "size": [2, 1.0, 1] }),
+        ];
+        let ast = AST::parse(Tokenizer::lexer("size:1").unwrap());
+        let mut options = FacetOptions::default();
+        options.set_fields(vec!["size"]);
+        let facets = Evaluator::default()
+            .facets(&ast, documents, &options)
+            .unwrap();
+        assert_eq!(facets.documents.len(), 3);
+        assert_eq!(
+            facet_counts(&facets),
+            vec![("size".to_string(), vec![(json!(1), 3), (json!(2), 1)])]
+        );
+    }
+
+    /// Number of values of `category`, the default field, counted with its
+    /// own conditions excluded, and of the matching documents.
+    fn category_facet(payload: &str, documents: &[Value]) -> (usize, usize) {
+        let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+        let mut options = FacetOptions::default();
+        options
+            .set_fields(vec!["category"])
+            .set_exclude_own_conditions(true);
+        let facets =
+            Evaluator::new(EvaluatorOptions::default().set_default_fields(vec!["category"]))
+                .facets(&ast, documents.to_vec(), &options)
+                .unwrap();
+        (facet_counts(&facets)[0].1.len(), facets.documents.len())
+    }
+
+    #[test]
+    fn evaluator_facets_wildcard() {
+        let documents = vec![
+            json!({ "brand": "acme", "category": "laptop" }),
+            json!({ "brand": "bolt", "category": "phone" }),
+            json!({ "brand": "acorn", "category": "laptops" }),
+        ];
+        let ast = AST::parse(Tokenizer::lexer("brand:ac*").unwrap());
+        let mut options = FacetOptions::default();
+        options
+            .set_fields(vec!["brand"])
+            .set_exclude_own_conditions(true);
+
+        let facets = Evaluator::default()
+            .facets(&ast, documents.clone(), &options)
+            .unwrap();
+        assert_eq!(
+            facets.documents,
+            vec![documents[0].clone(), documents[2].clone()]
+        );
+        assert_eq!(facet_counts(&facets)[0].1.len(), 3);
+        assert_eq!(category_facet("lapt*", &documents), (3, 2));
+    }
+
+    #[test]
+    fn evaluator_facets_fuzzy() {
+        let documents = vec![
+            json!({ "category": "color" }),
+            json!({ "category": "phone" }),
+            json!({ "category": "colour" }),
+        ];
+        assert_eq!(category_facet("colr~", &documents), (3, 2));
+    }
+
+    #[test]
+    fn evaluator_facets_phrase() {
+        let documents = vec![
+            json!({ "category": "a b" }),
+            json!({ "category": "c" }),
+            json!({ "category": "a x b" }),
+        ];
+        assert_eq!(category_facet(r#""a b"~1"#, &documents), (3, 2));
+    }
+
+    #[test]
+    fn evaluator_regex() {
+        let document = json!({ "name": "Abc/xyz", "tags": ["red", "blue"], "price": 10 });
+        assert!(matches(r#"name =~ /^ab.*z$/i"#, &document));
+        assert!(!matches(r#"name =~ /^ab.*z$/"#, &document));
+        assert!(matches(r#"name =~ /c\/x/"#, &document));
+        assert!(matches(r#"tags =~ /^bl/ tags !~ /^gr/"#, &document));
+        assert!(!matches(r#"price =~ /10/"#, &document));
+        assert!(matches(r#"missing !~ /./"#, &document));
+        assert!(matches(r#"name =~ "^Ab" tags =~ "e""#, &document));
+
+        let tokens = Tokenizer::lexer("name =~ /(a/ price > 1").unwrap();
+        assert_matches!(
+            Evaluator::default().matches(&AST::parse(tokens), &document),
+            Err(EvaluatorError::InvalidRegex(_, span)) if span.start == 8 && span.end == 12
+        );
+        let tokens = Tokenizer::lexer("name =~ /a/g").unwrap();
+        assert_matches!(
+            Evaluator::default().matches(&AST::parse(tokens), &document),
+            Err(EvaluatorError::InvalidRegex(_, _))
+        );
+    }
+
+    #[test]
+    fn evaluator_wildcard() {
+        let document =
+            json!({ "title": "Best Laptop-Pro", "brand": "acme", "tags": ["red", "blue"] });
+        assert!(matches("lapt*", &document));
+        assert!(matches("*top pr?", &document));
+        assert!(!matches("lapt?", &document));
+        assert!(!matches("aptop*", &document));
+        assert!(matches("brand:ac* brand:a??e", &document));
+        assert!(!matches("brand:AC*", &document));
+        assert!(!matches("brand:ac?", &document));
+        assert!(matches("tags:bl*", &document));
+        assert!(matches("-tags:gr*", &document));
+    }
+
+    #[test]
+    fn evaluator_fuzzy() {
+        let document =
+            json!({ "title": "Red Colour Pencil", "name": "colour", "tags": ["kitten"] });
+        assert!(matches("color~1", &document));
+        assert!(matches("COLR~", &document));
+        assert!(!matches("colr~1", &document));
+        assert!(!matches("pencel~0", &document));
+        assert!(matches("name:colr~2 name:colour~0", &document));
+        assert!(!matches("name:Colour~0", &document));
+        assert!(!matches("tags:sitting~3", &document));
+        assert!(matches("tags:sittin~2", &document));
+        assert!(!matches("name:coloured~1", &document));
+        // Distances are at most 2 edits.
+        assert!(matches("name:colours~18446744073709551615", &document));
+        assert!(!matches("name:colourful~18446744073709551615", &document));
+    }
+
+    #[test]
+    fn evaluator_phrase() {
+        let document = json!({
+            "title": "The quick brown fox jumps over the lazy dog",
+            "tags": ["Quick-Fox", "slow"],
+        });
+        assert!(matches(r#"title:"quick brown""#, &document));
+        assert!(!matches(r#"title:"quick fox""#, &document));
+        assert!(!matches(r#"title:"brown quick""#, &document));
+        assert!(matches(r#"tags:"quick fox""#, &document));
+        assert!(matches(r#""quick fox"~1"#, &document));
+        assert!(!matches(r#""quick jumps"~1"#, &document));
+        assert!(matches(r#""quick fox over"~3"#, &document));
+        assert!(!matches(r#""quick fox over"~1"#, &document));
+        assert!(matches(r#"title:"the lazy dog"~0"#, &document));
+        assert!(!matches(r#""fox quick"~3"#, &document));
+        // A phrase without terms matches nothing.
+        assert!(!matches(r#"title:"- -""#, &document));
+        assert!(!matches(r#""- -"~1"#, &document));
+        assert!(matches(r#""quick over"~18446744073709551615"#, &document));
+        assert!(Tokenizer::lexer(r#""quick over"~99999999999999999999999"#).is_err());
+    }
+
+    fn ranked(payload: &str, documents: &[Value], options: &RankOptions) -> Vec<Value> {
+        let tokens = Tokenizer::lexer(payload).unwrap();
+        Evaluator::default()
+            .rank(&AST::parse(tokens), documents.to_vec(), options)
+            .unwrap()
+            .into_iter()
+            .map(|(_, document)| document["id"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn evaluator_rank() {
+        let documents = vec![
+            json!({ "id": 1, "title": "red car", "body": "a fast car" }),
+            json!({ "id": 2, "title": "blue car", "body": "red seats and a red roof" }),
+            json!({ "id": 3, "title": "red bike" }),
+            json!({ "id": 4, "title": "green car" }),
+        ];
+        let options = RankOptions::default();
+        assert_eq!(
+            ranked("red", &documents, &options),
+            vec![json!(1), json!(3)]
+        );
+        assert_eq!(
+            ranked("car OR red", &documents, &options),
+            vec![json!(1), json!(3), json!(2), json!(4)]
+        );
+        assert_eq!(
+            ranked("car^4 OR red", &documents, &options),
+            vec![json!(1), json!(2), json!(4), json!(3)]
+        );
+
+        let mut options = RankOptions::default();
+        options.set_fields(vec!["title", "body"]);
+        assert_eq!(
+            ranked("car OR body:red", &documents, &options),
+            vec![json!(2), json!(1), json!(4)]
+        );
+
+        let tokens = Tokenizer::lexer("car").unwrap();
+        let scores: Vec<f64> = Evaluator::default()
+            .rank(&AST::parse(tokens), documents.clone(), &options)
+            .unwrap()
+            .into_iter()
+            .map(|(score, _)| score)
+            .collect();
+        assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(scores.iter().all(|score| *score > 0.0));
+    }
+
+    #[test]
+    fn evaluator_analyzer() {
+        let mut options = EvaluatorOptions::default();
+        options
+            .set_default_fields(vec!["title", "body"])
+            .set_analyzer("title", Arc::new(TextAnalyzer::english()));
+        let evaluator = Evaluator::new(&options);
+        let matches = |payload: &str, document: &Value| {
+            let tokens = Tokenizer::lexer(payload).unwrap();
+            evaluator.matches(&AST::parse(tokens), document).unwrap()
+        };
+
+        let document = json!({ "title": "Café Running Shoes", "body": "Café" });
+        assert!(matches("cafe", &document));
+        assert!(matches("cafe*", &document));
+        assert!(matches(r#""run shoe"~0"#, &document));
+        assert!(matches(r#"title:"running the shoes""#, &document));
+        assert!(!matches(r#"body:"cafe""#, &document));
+        assert!(!matches("cafe", &json!({ "body": "Café" })));
+    }
+
+    #[test]
+    fn evaluator_highlight() {
+        let mut options = EvaluatorOptions::default();
+        options.set_default_fields(vec!["title", "tags"]);
+        let evaluator = Evaluator::new(&options);
+        let highlight = |payload: &str, document: &Value| {
+            let tokens = Tokenizer::lexer(payload).unwrap();
+            evaluator
+                .highlight(&AST::parse(tokens), document)
+                .unwrap()
+                .map(|highlights| {
+                    highlights
+                        .into_iter()
+                        .map(|highlight| {
+                            let ranges = highlight.ranges.iter();
+                            let ranges = ranges.map(|range| (range.start, range.end));
+                            (highlight.field, ranges.collect::<Vec<_>>())
+                        })
+                        .collect::<Vec<_>>()
+                })
+        };
+
+        let document = json!({
+            "title": "Café racer, the quick brown fox",
+            "tags": ["vintage", "racing"],
+            "brand": "Acme",
+        });
+        assert_eq!(highlight("bolt", &document), None);
+        assert_eq!(
+            highlight("CAF rac*", &document),
+            Some(vec![
+                ("title".to_string(), vec![(0, 3), (5, 10)]),
+                ("tags.1".to_string(), vec![(0, 6)]),
+            ])
+        );
+        assert_eq!(
+            highlight(r#""quick fox"~1 brand:Ac*"#, &document),
+            Some(vec![
+                ("title".to_string(), vec![(16, 31)]),
+                ("brand".to_string(), vec![(0, 4)]),
+            ])
+        );
+        assert_eq!(
+            highlight("vintaje~1 -bolt title =~ /r[a-z]+/", &document),
+            Some(vec![
+                ("tags.0".to_string(), vec![(0, 7)]),
+                ("title".to_string(), vec![(5, 10), (23, 27)]),
+            ])
+        );
+
+        // Each term keeps the ranges it satisfied, with its position in the
+        // query.
+        let tokens = Tokenizer::lexer("racer rac* title:\"the quick\"").unwrap();
+        let highlights = evaluator
+            .highlight(&AST::parse(tokens), &document)
+            .unwrap()
+            .unwrap();
+        assert_eq!(highlights[0].field, "title");
+        assert_eq!(highlights[0].ranges, vec![5..10, 12..21]);
+        let terms: Vec<_> = highlights[0]
+            .terms
+            .iter()
+            .map(|term| {
+                let ranges = term.ranges.iter().map(|range| (range.start, range.end));
+                (term.span.start, ranges.collect::<Vec<_>>())
+            })
+            .collect();
+        assert_eq!(
+            terms,
+            vec![(0, vec![(5, 10)]), (6, vec![(5, 10)]), (11, vec![(12, 21)])]
+        );
+        assert_eq!(highlights[1].field, "tags.1");
+        assert_eq!(highlights[1].terms.len(), 1);
+        assert_eq!(highlights[1].terms[0].span.start, 6);
+
+        let tokens = Tokenizer::lexer("fox").unwrap();
+        let ast = AST::parse(tokens);
+        let highlights = evaluator.highlight(&ast, &document).unwrap().unwrap();
+        let mut options = SnippetOptions::default();
+        assert_eq!(
+            highlights[0].snippet(&document, &options),
+            "Café racer, the quick brown <em>fox</em>"
+        );
+        options.set_tags("[", "]").set_max_length(9);
+        assert_eq!(highlights[0].snippet(&document, &options), "…brown [fox]");
+    }
+
+    #[test]
+    fn evaluator_explain() {
+        let explain = |payload: &str, document: &Value| {
+            let tokens = Tokenizer::lexer(payload).unwrap();
+            Evaluator::default()
+                .explain(&AST::parse(tokens), document)
+                .unwrap()
+        };
+        let document = json!({ "brand": "acme", "price": 5, "tags": ["new"] });
+
+        let payload = "brand:acme price > 10 (tags:sale OR tags:used) -tags:new";
+        let explanation = explain(payload, &document);
+        assert!(!explanation.matched);
+        assert_eq!(explanation.conditions.len(), 4);
+        let condition = &explanation.conditions[1];
+        assert_eq!(condition.value, json!(false));
+        assert_eq!(condition.operands[0].value, json!(5));
+        assert_eq!(condition.operands[1].value, json!(10));
+        assert_eq!(
+            explanation.underline(payload),
+            [
+                payload,
+                "           ^^^^^^^^^^  ^^^^^^^^^    ^^^^^^^^^  ^^^^^^^^^",
+            ]
+            .join("\n")
+        );
+
+        let explanation = explain("brand:acme^2 tags:new", &document);
+        assert!(explanation.matched);
+        assert!(explanation.failures().is_empty());
+        assert_eq!(
+            explanation.conditions[0].conditions[0].operands[0].value,
+            json!("acme")
+        );
+    }
+
+    #[test]
+    fn evaluator_compiled_query() {
+        let documents = [
+            json!({
+                "title": "Red laptop bag",
+                "brand": "acme",
+                "price": 40,
+                "tags": ["red", "sale"],
+                "variants": [{ "size": "m", "stock": 2 }, { "size": "l", "stock": 0 }],
+                "sizes": [{ "values": ["s", "m"] }],
+            }),
+            json!({ "title": "Colour printer", "brand": "bolt", "price": 120.5, "tags": "new" }),
+            json!({ "brand": "core", "variants": { "size": "s", "stock": 1 } }),
+        ];
+        for payload in [
+            "laptop",
+            "lapt* colour~1",
+            r#""red bag" OR title:"colour printer""#,
+            "brand:ac* OR brand:bolt~1",
+            "price:10..50 -tags:new",
+            "price > 100 || brand in [\"core\"]",
+            "brand =~ /^A/i",
+            "brand !~ brand",
+            r#"brand =~ "^ac" OR title =~ brand"#,
+            r#"brand =~ "(""#,
+            r#"lower(brand) = "acme" `${brand}-${price}` = "acme-40""#,
+            r#"any(tags, t => t = "red")"#,
+            "all(variants.stock > 0)",
+            "any(variants.stock > 0) none(variants.size:xl)",
+            r#"any(variants, variants.stock > 0 && variants.size = "m")"#,
+            r#"any(sizes, s => any(s.values, v => v = "m" && s.values = "s"))"#,
+            "any(sizes.values, any(variants.stock > 1))",
+            r#"$.brand:acme tags in ["new", "x"]"#,
+            "missing(brand)",
+            "price:*..10 OR price:100..*",
+        ] {
+            let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+            let compiled = CompiledQuery::compile(&ast);
+            for document in &documents {
+                let expected = Evaluator::default().matches(&ast, document);
+                match (compiled.matches(document), expected) {
+                    (Ok(result), Ok(expected)) => {
+                        assert_eq!(result, expected, "{payload} {document}")
+                    }
+                    (Err(_), Err(_)) => {}
+                    (result, expected) => panic!("{payload}: {result:?} {expected:?}"),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod index_tests {
+    use ql::{
+        analyzer::TextAnalyzer, ast::AST, evaluator::EvaluatorOptions, index::IndexOptions,
+        Evaluator, Index, Tokenizer,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn documents() -> Vec<Value> {
+        vec![
+            json!({ "title": "Red laptop", "brand": "acme", "price": 900 }),
+            json!({ "title": "Blue laptop bag", "brand": "bolt", "price": 40 }),
+            json!({ "title": "Red bag", "brand": "acme", "price": [15, 25] }),
+            json!({ "title": "Quick brown fox", "brand": "core" }),
+            json!({ "title": "Lazy colour printer", "brand": "acme", "price": 120.5 }),
+        ]
+    }
+
+    fn index() -> Index {
+        let mut options = IndexOptions::default();
+        options
+            .set_text_fields(vec!["title", "brand"])
+            .set_numeric_fields(vec!["price"]);
+        let mut index = Index::new(&options);
+        for document in documents() {
+            index.insert(document);
+        }
+        index
+    }
+
+    fn candidates(index: &Index, payload: &str) -> Option<Vec<usize>> {
+        index.candidates(&AST::parse(Tokenizer::lexer(payload).unwrap()))
+    }
+
+    #[test]
+    fn index_search_matches_evaluator() {
+        let index = index();
+        let documents = documents();
+        for payload in [
+            "laptop",
+            "lapt",
+            "red bag",
+            "lapt* price < 100",
+            "color~1",
+            r#""quick fox"~1"#,
+            r#"title:"red bag""#,
+            "brand:acme price:100..*",
+            "brand:acme OR price = 40",
+            "price > 20 price <= 40",
+            "-laptop",
+            "laptop OR -bag",
+            "ue_laptop_ba",
+            "d_lap",
+            "red_laptop_bag",
+            "price:40..20",
+            "price:40<..<40",
+            "price:40..<40",
+            "price:40..40",
+            "any(price, p => p > 20)",
+            "",
+        ] {
+            let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+            assert_eq!(
+                index.search(&ast).unwrap(),
+                Evaluator::default().filter(&ast, &documents).unwrap(),
+                "{}",
+                payload
+            );
+        }
+    }
+
+    #[test]
+    fn index_search_without_words() {
+        // Free text without words is contained in strings without words.
+        let documents = vec![
+            json!({ "title": "Red - laptop" }),
+            json!({ "title": "Blue" }),
+            json!({ "title": "-" }),
+            json!({ "title": "" }),
+        ];
+        let mut index = Index::new(IndexOptions::default().set_text_fields(vec!["title"]));
+        for document in documents.clone() {
+            index.insert(document);
+        }
+        for payload in [r#""""#, "-", r#""-""#, "_", r#""-" laptop"#] {
+            let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+            assert_eq!(
+                index.search(&ast).unwrap(),
+                Evaluator::default().filter(&ast, &documents).unwrap(),
+                "{}",
+                payload
+            );
+        }
+        let ast = AST::parse(Tokenizer::lexer("-").unwrap());
+        assert_eq!(index.candidates(&ast), None);
+    }
+
+    #[test]
+    fn index_candidates() {
+        let index = index();
+        assert_eq!(candidates(&index, "laptop"), Some(vec![0, 1]));
+        assert_eq!(candidates(&index, "laptop price < 100"), Some(vec![1]));
+        assert_eq!(candidates(&index, "price:20..40"), Some(vec![1, 2]));
+        assert_eq!(
+            candidates(&index, "brand:acme OR price > 100"),
+            Some(vec![0, 2, 4])
+        );
+        assert_eq!(candidates(&index, "laptop -bag"), Some(vec![0, 1]));
+        assert_eq!(candidates(&index, "laptop OR -bag"), None);
+        assert_eq!(candidates(&index, "ue_laptop_ba"), Some(vec![1]));
+        assert_eq!(candidates(&index, "d_lap"), Some(vec![0]));
+        assert_eq!(candidates(&index, "d_bag_x"), Some(vec![]));
+        assert_eq!(candidates(&index, "price:40<..<40"), Some(vec![]));
+        assert_eq!(candidates(&index, "price:40..40"), Some(vec![1]));
+        assert_eq!(candidates(&index, "stock > 1"), None);
+        assert_eq!(index.len(), 5);
+    }
+
+    #[test]
+    fn index_analyzer() {
+        let mut evaluator = EvaluatorOptions::default();
+        evaluator.set_analyzer("title", Arc::new(TextAnalyzer::english()));
+        let mut options = IndexOptions::default();
+        options
+            .set_text_fields(vec!["title"])
+            .set_evaluator(evaluator.clone());
+        let mut index = Index::new(&options);
+        let documents = vec![
+            json!({ "title": "Café Running Shoes" }),
+            json!({ "title": "The run of the shoe" }),
+            json!({ "title": "Blue cafes" }),
+        ];
+        for document in documents.clone() {
+            index.insert(document);
+        }
+
+        for (payload, ids) in [
+            ("cafe", Some(vec![0, 2])),
+            ("runn", Some(vec![0])),
+            (r#""running shoes""#, Some(vec![0])),
+            (r#""run shoe"~2"#, Some(vec![0, 1])),
+            (r#"title:"the shoes""#, Some(vec![0, 1])),
+            ("caf*", Some(vec![0, 2])),
+        ] {
+            let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+            assert_eq!(index.candidates(&ast), ids, "{}", payload);
+            assert_eq!(
+                index.search(&ast).unwrap(),
+                Evaluator::new(&evaluator).filter(&ast, &documents).unwrap(),
+                "{}",
+                payload
+            );
+        }
+    }
+}
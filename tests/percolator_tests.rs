@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod percolator_tests {
+    use ql::{ast::AST, evaluator::EvaluatorOptions, Evaluator, Percolator, Tokenizer};
+    use serde_json::{json, Value};
+
+    const QUERIES: [&str; 16] = [
+        "brand:acme",
+        "brand:acme price < 100",
+        "laptop",
+        "brand:bolt OR brand:core",
+        "status in [\"open\", \"pending\"]",
+        "price:10..20",
+        "price = 40",
+        "tags:red",
+        "brand:ac*",
+        "name =~ /^lap/i",
+        "-laptop",
+        "brand:acme OR -used",
+        "any(tags, t => t = \"blue\")",
+        "variants.size:m",
+        r#"title:"red bag""#,
+        "in_stock:true",
+    ];
+
+    fn documents() -> Vec<Value> {
+        vec![
+            json!({ "title": "Red laptop", "brand": "acme", "price": 900 }),
+            json!({ "title": "Blue laptop bag", "brand": "bolt", "price": 40.0 }),
+            json!({ "title": "Red bag", "brand": "acme", "price": [15, 25], "tags": ["red"] }),
+            json!({ "name": "Laptop stand", "status": "open", "in_stock": true }),
+            json!({ "variants": [{ "size": "s" }, { "size": "m" }], "tags": ["blue"] }),
+            json!({}),
+        ]
+    }
+
+    fn percolator() -> Percolator {
+        let mut percolator = Percolator::new(&EvaluatorOptions::default());
+        for payload in QUERIES {
+            percolator.insert(AST::parse(Tokenizer::lexer(payload).unwrap()));
+        }
+        percolator
+    }
+
+    #[test]
+    fn percolator_matches_evaluator() {
+        let percolator = percolator();
+        for document in documents() {
+            let expected: Vec<usize> = QUERIES
+                .iter()
+                .enumerate()
+                .filter(|(_, payload)| {
+                    let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+                    Evaluator::default().matches(&ast, &document).unwrap()
+                })
+                .map(|(id, _)| id)
+                .collect();
+            assert_eq!(
+                percolator.percolate(&document).unwrap(),
+                expected,
+                "{document}"
+            );
+        }
+    }
+
+    #[test]
+    fn percolator_negative_zero() {
+        let queries = [
+            "price:0",
+            "price = 0",
+            "price in [0]",
+            "price:-0",
+            "price = 0.0",
+        ];
+        let mut percolator = Percolator::new(&EvaluatorOptions::default());
+        for payload in queries {
+            percolator.insert(AST::parse(Tokenizer::lexer(payload).unwrap()));
+        }
+        for document in [
+            json!({ "price": -0.0 }),
+            json!({ "price": 0 }),
+            json!({ "price": [1, -0.0] }),
+        ] {
+            let expected: Vec<usize> = queries
+                .iter()
+                .enumerate()
+                .filter(|(_, payload)| {
+                    let ast = AST::parse(Tokenizer::lexer(payload).unwrap());
+                    Evaluator::default().matches(&ast, &document).unwrap()
+                })
+                .map(|(id, _)| id)
+                .collect();
+            assert_eq!(expected.len(), queries.len(), "{document}");
+            assert_eq!(
+                percolator.percolate(&document).unwrap(),
+                expected,
+                "{document}"
+            );
+        }
+    }
+
+    #[test]
+    fn percolator_candidates() {
+        let percolator = percolator();
+        assert_eq!(percolator.len(), QUERIES.len());
+        assert_eq!(percolator.candidates(&json!({})), vec![10, 11, 12]);
+        assert_eq!(
+            percolator.candidates(&json!({ "brand": "core", "price": 15 })),
+            vec![3, 5, 8, 10, 11, 12]
+        );
+        assert_eq!(
+            percolator.candidates(&json!({ "title": "Red bag", "brand": "acme" })),
+            vec![0, 1, 2, 8, 10, 11, 12, 14]
+        );
+    }
+}
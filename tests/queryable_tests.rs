@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod queryable_tests {
+    use assert_matches::assert_matches;
+    use ql::{
+        ast::AST,
+        evaluator::{EvaluatorError, EvaluatorOptions},
+        Evaluator, Queryable, Tokenizer,
+    };
+    use serde_json::{json, Value};
+
+    #[derive(Queryable)]
+    struct Variant {
+        size: String,
+        stock: u32,
+    }
+
+    #[derive(Queryable)]
+    struct Product {
+        name: String,
+        brand: Option<String>,
+        price: f64,
+        #[queryable(rename = "tags")]
+        labels: Vec<String>,
+        variants: Vec<Variant>,
+        #[queryable(skip)]
+        #[allow(dead_code)]
+        cost: f64,
+    }
+
+    const QUERIES: [&str; 12] = [
+        "brand:acme",
+        "brand:acme price < 100",
+        "laptop",
+        "price:10..50",
+        "tags:red",
+        "brand:ac*",
+        "name =~ /^lap/i",
+        "-brand:acme",
+        "any(tags, t => t = \"blue\")",
+        "variants.size:m",
+        "all(variants, v => v.stock > 0)",
+        "`${brand} ${name}` = \"acme Laptop\"",
+    ];
+
+    fn products() -> Vec<Product> {
+        vec![
+            Product {
+                name: "Laptop".to_string(),
+                brand: Some("acme".to_string()),
+                price: 900.0,
+                labels: vec!["red".to_string()],
+                variants: vec![Variant {
+                    size: "m".to_string(),
+                    stock: 3,
+                }],
+                cost: 700.0,
+            },
+            Product {
+                name: "Laptop bag".to_string(),
+                brand: None,
+                price: 40.0,
+                labels: vec!["blue".to_string(), "red".to_string()],
+                variants: vec![
+                    Variant {
+                        size: "s".to_string(),
+                        stock: 0,
+                    },
+                    Variant {
+                        size: "m".to_string(),
+                        stock: 2,
+                    },
+                ],
+                cost: 10.0,
+            },
+            Product {
+                name: "Stand".to_string(),
+                brand: Some("acme".to_string()),
+                price: 25.5,
+                labels: vec![],
+                variants: vec![],
+                cost: 5.0,
+            },
+        ]
+    }
+
+    fn documents() -> Vec<Value> {
+        vec![
+            json!({
+                "name": "Laptop", "brand": "acme", "price": 900.0, "tags": ["red"],
+                "variants": [{ "size": "m", "stock": 3 }],
+            }),
+            json!({
+                "name": "Laptop bag", "brand": null, "price": 40.0, "tags": ["blue", "red"],
+                "variants": [{ "size": "s", "stock": 0 }, { "size": "m", "stock": 2 }],
+            }),
+            json!({
+                "name": "Stand", "brand": "acme", "price": 25.5, "tags": [], "variants": [],
+            }),
+        ]
+    }
+
+    fn ast(payload: &str) -> AST {
+        AST::parse(Tokenizer::lexer(payload).unwrap())
+    }
+
+    #[test]
+    fn queryable_matches_json() {
+        let evaluator =
+            Evaluator::new(EvaluatorOptions::default().set_default_fields(vec!["name"]));
+        let products = products();
+        let documents = documents();
+        for payload in QUERIES {
+            let ast = ast(payload);
+            let expected: Vec<_> = evaluator
+                .filter(&ast, &documents)
+                .unwrap()
+                .into_iter()
+                .map(|document| document["name"].clone())
+                .collect();
+            let actual: Vec<_> = evaluator
+                .filter(&ast, &products)
+                .unwrap()
+                .into_iter()
+                .map(|product| json!(product.name))
+                .collect();
+            assert_eq!(actual, expected, "{payload}");
+        }
+    }
+
+    #[test]
+    fn queryable_get_field() {
+        let products = products();
+        let path = |path: &str| path.split('.').map(|s| s.to_string()).collect::<Vec<_>>();
+        let get = |path: Vec<String>| products[1].get_field(&path).map(Value::from);
+        assert_eq!(get(path("variants.stock")), Some(json!([0, 2])));
+        assert_eq!(get(path("variants.1.size")), Some(json!("m")));
+        assert_eq!(get(path("brand")), None);
+        assert_eq!(get(path("cost")), None);
+        assert_eq!(
+            products[2].get_field(&[]).map(Value::from),
+            Some(documents()[2].clone())
+        );
+    }
+
+    #[test]
+    fn queryable_unknown_field() {
+        let products = products();
+        let evaluator = Evaluator::default();
+        let error = |payload: &str| match evaluator.filter(&ast(payload), &products) {
+            Err(EvaluatorError::UnknownField(path, _)) => Some(path),
+            Err(error) => panic!("{payload}: {error:?}"),
+            Ok(_) => None,
+        };
+        assert_eq!(error("colour:red"), Some("colour".to_string()));
+        assert_eq!(error("cost < 10"), Some("cost".to_string()));
+        assert_eq!(error("labels:red"), Some("labels".to_string()));
+        assert_eq!(error("price.amount > 1"), Some("price.amount".to_string()));
+        assert_eq!(
+            error("any(variants, v => v.colour = \"red\")"),
+            Some("variants.colour".to_string())
+        );
+        assert_eq!(error("variants.stock > 1 $.tags:red"), None);
+        // Free text is looked up in the default fields, `title` by default.
+        assert_eq!(error("laptop"), Some("title".to_string()));
+        assert_eq!(error(r#"name:lap* name:lapto~1 name:"laptop bag""#), None);
+        assert_eq!(error("price > 10 OR lap*"), Some("title".to_string()));
+        assert_matches!(evaluator.filter(&ast("colour:red"), &documents()), Ok(_));
+
+        assert_matches!(
+            evaluator.matches(&ast("colour:red"), &products[0]),
+            Err(EvaluatorError::UnknownField(path, _)) if path == "colour"
+        );
+        assert_matches!(
+            evaluator.matches(&ast(r#""red laptop""#), &products[0]),
+            Err(EvaluatorError::UnknownField(path, _)) if path == "title"
+        );
+        let evaluator =
+            Evaluator::new(EvaluatorOptions::default().set_default_fields(vec!["name", "tags"]));
+        assert_matches!(evaluator.matches(&ast("laptop"), &products[0]), Ok(true));
+    }
+}
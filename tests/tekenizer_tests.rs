@@ -215,4 +215,70 @@ mod tekenizer_tests {
         let tokens = Tokenizer::lexer(payload);
         assert_debug_snapshot!(tokens);
     }
+
+    #[test]
+    fn tokenizer_lexer_range() {
+        let payload = "price:10..20 price:1.5..*";
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+    }
+
+    #[test]
+    fn tokenizer_lexer_range_exclusive() {
+        let payload = "price:-10<..<-1.5 price > -1";
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+    }
+
+    #[test]
+    fn tokenizer_lexer_date_range() {
+        let payload = "date:2024-01-01..2024-12-31";
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+    }
+
+    #[test]
+    fn tokenizer_lexer_compare() {
+        let payload = r#"price:>=10 price <= 20 status in ["open", "pending"]"#;
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+    }
+
+    #[test]
+    fn tokenizer_lexer_negation() {
+        let payload = "-term !field:value NOT field:value";
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+    }
+
+    #[test]
+    fn tokenizer_lexer_regex() {
+        let payload = r#"name =~ /^a[/]b\/.*z$/i sku !~ /x/ price / 2"#;
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+    }
+
+    #[test]
+    fn tokenizer_lexer_wildcard() {
+        let payload = r#"lapt* col?r brand:ac* price:10..* 2 * 3"#;
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+    }
+
+    #[test]
+    fn tokenizer_lexer_fuzzy() {
+        let payload = r#"colour~1 name:"colr"~ x !~ /y/"#;
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+        assert!(Tokenizer::lexer("colour~99999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn tokenizer_lexer_boost() {
+        let payload = r#"quick^2 "fox"^0.5 title:fox^3"#;
+        let tokens = Tokenizer::lexer(payload);
+        assert_debug_snapshot!(tokens);
+        assert!(Tokenizer::lexer("quick^").is_err());
+        assert!(Tokenizer::lexer("quick^1.2.3").is_err());
+    }
 }